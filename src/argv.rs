@@ -0,0 +1,85 @@
+/// Default field separators when `IFS` is not set.
+pub(crate) const DEFAULT_IFS: &str = " \t\n";
+
+/// Builds an argument list from pieces of text, like the shell does before `exec`.
+///
+/// Quoted text is appended as-is, while the results of unquoted expansions are
+/// split on `IFS`. A word exists once any text, even an empty quoted string, is
+/// added to it, so `""` gives an empty argument but an empty `$VAR` gives none.
+#[derive(Debug, Default)]
+pub(crate) struct Fields {
+    fields: Vec<String>,
+    current: String,
+    /// Whether the current word has started
+    started: bool,
+}
+
+impl Fields {
+    /// Append text that is not subject to splitting.
+    pub(crate) fn push_quoted(&mut self, text: &str) {
+        self.current.push_str(text);
+        self.started = true;
+    }
+
+    /// End the current word, if any.
+    pub(crate) fn end_word(&mut self) {
+        if self.started {
+            self.fields.push(std::mem::take(&mut self.current));
+            self.started = false;
+        }
+    }
+
+    /// Append unquoted source text: blanks separate words and a backslash
+    /// quotes the next character.
+    pub(crate) fn push_unquoted(&mut self, text: &str) {
+        let mut chars = text.chars();
+        while let Some(c) = chars.next() {
+            match c {
+                ' ' | '\t' | '\n' => self.end_word(),
+                '\\' => match chars.next() {
+                    Some(escaped) => self.push_char(escaped),
+                    None => self.push_char('\\'),
+                },
+                _ => self.push_char(c),
+            }
+        }
+    }
+
+    /// Append the result of an unquoted expansion, splitting it on the characters
+    /// in `ifs`. Runs of IFS whitespace count as one separator, while every other
+    /// IFS character separates a field, so `a,,b` with `IFS=,` gives an empty field.
+    pub(crate) fn push_split(&mut self, value: &str, ifs: &str) {
+        // True right after whitespace ended a word, which then absorbs one
+        // following non-whitespace separator
+        let mut after_whitespace = false;
+        for c in value.chars() {
+            if !ifs.contains(c) {
+                self.push_char(c);
+                after_whitespace = false;
+            } else if c.is_whitespace() {
+                if self.started {
+                    self.end_word();
+                    after_whitespace = true;
+                }
+            } else if self.started {
+                self.end_word();
+            } else if after_whitespace {
+                after_whitespace = false;
+            } else {
+                // Empty field between two separators
+                self.fields.push(String::new());
+            }
+        }
+    }
+
+    /// End the last word and return all fields.
+    pub(crate) fn finish(mut self) -> Vec<String> {
+        self.end_word();
+        self.fields
+    }
+
+    fn push_char(&mut self, c: char) {
+        self.current.push(c);
+        self.started = true;
+    }
+}
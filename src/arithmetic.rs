@@ -0,0 +1,345 @@
+use crate::error::Error;
+
+/// Lexical token of an arithmetic expression.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Tok<'e> {
+    Num(i64),
+    Ident(&'e str),
+    Op(&'static str),
+}
+
+/// Operators, longest first so that `**` wins over `*`.
+const OPERATORS: &[&str] = &[
+    "**", "<<", ">>", "<=", ">=", "==", "!=", "&&", "||",
+    "+", "-", "*", "/", "%", "<", ">", "&", "|", "^", "!", "~", "?", ":", ",", "(", ")",
+];
+
+/// Deepest nesting of parentheses, unary operators, `**` and `?:` in an
+/// expression, so that `((((...))))` is an error rather than a stack overflow.
+const MAX_NESTING: usize = 200;
+
+/// Evaluate an arithmetic expression as in shell `$((expr))`.
+///
+/// Supports 64-bit integers (decimal, `0x` hex, `0` octal and `base#digits`),
+/// the usual C operators including `**`, comparisons, bit operations and the
+/// ternary operator. Bare identifiers are resolved through `lookup`.
+/// `pos` is the position of the expression in the input, used for errors.
+pub(crate) fn evaluate(expr: &str, pos: usize, lookup: &dyn Fn(&str) -> Result<i64, Error>) -> Result<i64, Error> {
+    let tokens = tokenize(expr, pos)?;
+    if tokens.is_empty() {
+        // An empty expression evaluates to 0
+        return Ok(0);
+    }
+
+    let mut parser = Parser { tokens, idx: 0, pos, depth: 0, lookup };
+    let value = parser.comma(true)?;
+    match parser.tokens.get(parser.idx) {
+        None => Ok(value),
+        Some(&(tok, at)) => Err(Error::ArithmeticError(format!("unexpected token {}", describe(tok)), at)),
+    }
+}
+
+/// Names an expression refers to without `$`, like `PORT` in `PORT + 1`.
+pub(crate) fn identifiers(expr: &str) -> Vec<&str> {
+    let mut names = Vec::new();
+    let mut rest = expr;
+
+    while let Some(c) = rest.chars().next() {
+        let len = if c.is_ascii_digit() {
+            // Skip numbers like 0x1F and 16#ff as a whole
+            rest.find(|c: char| !(c.is_ascii_alphanumeric() || c == '#' || c == '_')).unwrap_or(rest.len())
+        } else if c.is_ascii_alphabetic() || c == '_' {
+            let len = rest.find(|c: char| !(c.is_ascii_alphanumeric() || c == '_')).unwrap_or(rest.len());
+            names.push(&rest[..len]);
+            len
+        } else {
+            c.len_utf8()
+        };
+        rest = &rest[len..];
+    }
+    names
+}
+
+fn tokenize(expr: &str, pos: usize) -> Result<Vec<(Tok<'_>, usize)>, Error> {
+    let mut tokens = Vec::new();
+    let mut rest = expr;
+
+    loop {
+        rest = rest.trim_start();
+        let at = pos + expr.len() - rest.len();
+        let Some(c) = rest.chars().next() else {
+            return Ok(tokens);
+        };
+
+        if c.is_ascii_digit() {
+            let len = rest
+                .find(|c: char| !(c.is_ascii_alphanumeric() || c == '#' || c == '_'))
+                .unwrap_or(rest.len());
+            tokens.push((Tok::Num(parse_number(&rest[..len], at)?), at));
+            rest = &rest[len..];
+        } else if c.is_ascii_alphabetic() || c == '_' {
+            let len = rest
+                .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+                .unwrap_or(rest.len());
+            tokens.push((Tok::Ident(&rest[..len]), at));
+            rest = &rest[len..];
+        } else if let Some(op) = OPERATORS.iter().find(|op| rest.starts_with(**op)) {
+            tokens.push((Tok::Op(op), at));
+            rest = &rest[op.len()..];
+        } else {
+            return Err(Error::ArithmeticError(format!("unexpected character {:?}", c), at));
+        }
+    }
+}
+
+fn parse_number(text: &str, at: usize) -> Result<i64, Error> {
+    let (digits, radix) = if let Some((base, digits)) = text.split_once('#') {
+        match base.parse::<u32>() {
+            Ok(radix @ 2..=36) => (digits, radix),
+            _ => return Err(Error::ArithmeticError(format!("invalid arithmetic base in {:?}", text), at)),
+        }
+    } else if let Some(hex) = text.strip_prefix("0x").or_else(|| text.strip_prefix("0X")) {
+        (hex, 16)
+    } else if text.len() > 1 && text.starts_with('0') {
+        (&text[1..], 8)
+    } else {
+        (text, 10)
+    };
+
+    // Wrap around on overflow like the shell does
+    let mut value: i64 = 0;
+    for c in digits.chars() {
+        match c.to_digit(radix) {
+            Some(d) => value = value.wrapping_mul(radix as i64).wrapping_add(d as i64),
+            None => return Err(Error::ArithmeticError(format!("invalid number {:?}", text), at)),
+        }
+    }
+    if digits.is_empty() {
+        return Err(Error::ArithmeticError(format!("invalid number {:?}", text), at));
+    }
+    Ok(value)
+}
+
+fn describe(tok: Tok<'_>) -> String {
+    match tok {
+        Tok::Num(n) => n.to_string(),
+        Tok::Ident(name) => name.to_string(),
+        Tok::Op(op) => format!("'{}'", op),
+    }
+}
+
+/// Binding power of binary operators; higher binds tighter.
+fn precedence(op: &str) -> Option<u8> {
+    Some(match op {
+        "||" => 1,
+        "&&" => 2,
+        "|" => 3,
+        "^" => 4,
+        "&" => 5,
+        "==" | "!=" => 6,
+        "<" | ">" | "<=" | ">=" => 7,
+        "<<" | ">>" => 8,
+        "+" | "-" => 9,
+        "*" | "/" | "%" => 10,
+        _ => return None,
+    })
+}
+
+/// Recursive descent evaluator. The `eval` flag is false in branches that are
+/// skipped by short-circuiting, where lookups and division errors are suppressed.
+struct Parser<'e, 'l> {
+    tokens: Vec<(Tok<'e>, usize)>,
+    idx: usize,
+    pos: usize,
+    /// Current nesting, see `MAX_NESTING`
+    depth: usize,
+    lookup: &'l dyn Fn(&str) -> Result<i64, Error>,
+}
+
+impl Parser<'_, '_> {
+    fn peek_op(&self) -> Option<&'static str> {
+        match self.tokens.get(self.idx) {
+            Some(&(Tok::Op(op), _)) => Some(op),
+            _ => None,
+        }
+    }
+
+    fn eat(&mut self, op: &str) -> bool {
+        if self.peek_op() == Some(op) {
+            self.idx += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Position of the current token, or the end of the expression.
+    fn at(&self) -> usize {
+        self.tokens.get(self.idx).map_or_else(
+            || self.tokens.last().map_or(self.pos, |&(_, at)| at),
+            |&(_, at)| at,
+        )
+    }
+
+    fn expect(&mut self, op: &str) -> Result<(), Error> {
+        if self.eat(op) {
+            Ok(())
+        } else {
+            Err(Error::ArithmeticError(format!("expected '{}'", op), self.at()))
+        }
+    }
+
+    /// Parse a nested part of the expression with `f`, failing if nesting is too deep.
+    fn nested(&mut self, f: impl FnOnce(&mut Self) -> Result<i64, Error>) -> Result<i64, Error> {
+        if self.depth == MAX_NESTING {
+            return Err(Error::ArithmeticError("expression nested too deeply".to_string(), self.at()));
+        }
+        self.depth += 1;
+        let result = f(self);
+        self.depth -= 1;
+        result
+    }
+
+    fn comma(&mut self, eval: bool) -> Result<i64, Error> {
+        let mut value = self.ternary(eval)?;
+        while self.eat(",") {
+            value = self.ternary(eval)?;
+        }
+        Ok(value)
+    }
+
+    fn ternary(&mut self, eval: bool) -> Result<i64, Error> {
+        let cond = self.binary(1, eval)?;
+        if !self.eat("?") {
+            return Ok(cond);
+        }
+        let then = self.nested(|p| p.comma(eval && cond != 0))?;
+        self.expect(":")?;
+        let otherwise = self.nested(|p| p.ternary(eval && cond == 0))?;
+        Ok(if cond != 0 { then } else { otherwise })
+    }
+
+    fn binary(&mut self, min_prec: u8, eval: bool) -> Result<i64, Error> {
+        let mut lhs = self.power(eval)?;
+
+        while let Some(op) = self.peek_op() {
+            let Some(prec) = precedence(op).filter(|&p| p >= min_prec) else {
+                break;
+            };
+            let at = self.at();
+            self.idx += 1;
+
+            lhs = match op {
+                "&&" => {
+                    let rhs = self.binary(prec + 1, eval && lhs != 0)?;
+                    (lhs != 0 && rhs != 0) as i64
+                },
+                "||" => {
+                    let rhs = self.binary(prec + 1, eval && lhs == 0)?;
+                    (lhs != 0 || rhs != 0) as i64
+                },
+                _ => {
+                    let rhs = self.binary(prec + 1, eval)?;
+                    apply(op, lhs, rhs, eval, at)?
+                },
+            };
+        }
+
+        Ok(lhs)
+    }
+
+    /// `**` is right associative and binds tighter than the other binary
+    /// operators, but looser than unary operators (`-2**2` is 4).
+    fn power(&mut self, eval: bool) -> Result<i64, Error> {
+        let base = self.unary(eval)?;
+        let at = self.at();
+        if !self.eat("**") {
+            return Ok(base);
+        }
+        let exp = self.nested(|p| p.power(eval))?;
+        if exp < 0 {
+            return if eval {
+                Err(Error::ArithmeticError("exponent less than 0".to_string(), at))
+            } else {
+                Ok(0)
+            };
+        }
+        Ok(base.wrapping_pow(exp.min(u32::MAX as i64) as u32))
+    }
+
+    fn unary(&mut self, eval: bool) -> Result<i64, Error> {
+        match self.peek_op() {
+            Some("-") => {
+                self.idx += 1;
+                Ok(self.nested(|p| p.unary(eval))?.wrapping_neg())
+            },
+            Some("+") => {
+                self.idx += 1;
+                self.nested(|p| p.unary(eval))
+            },
+            Some("!") => {
+                self.idx += 1;
+                Ok((self.nested(|p| p.unary(eval))? == 0) as i64)
+            },
+            Some("~") => {
+                self.idx += 1;
+                Ok(!self.nested(|p| p.unary(eval))?)
+            },
+            _ => self.primary(eval),
+        }
+    }
+
+    fn primary(&mut self, eval: bool) -> Result<i64, Error> {
+        let at = self.at();
+        match self.tokens.get(self.idx).copied() {
+            Some((Tok::Num(n), _)) => {
+                self.idx += 1;
+                Ok(n)
+            },
+            Some((Tok::Ident(name), _)) => {
+                self.idx += 1;
+                if eval {
+                    (self.lookup)(name)
+                } else {
+                    Ok(0)
+                }
+            },
+            Some((Tok::Op("("), _)) => {
+                self.idx += 1;
+                let value = self.nested(|p| p.comma(eval))?;
+                self.expect(")")?;
+                Ok(value)
+            },
+            Some((tok, _)) => Err(Error::ArithmeticError(format!("unexpected token {}", describe(tok)), at)),
+            None => Err(Error::ArithmeticError("unexpected end of expression".to_string(), at)),
+        }
+    }
+}
+
+fn apply(op: &str, lhs: i64, rhs: i64, eval: bool, at: usize) -> Result<i64, Error> {
+    Ok(match op {
+        "|" => lhs | rhs,
+        "^" => lhs ^ rhs,
+        "&" => lhs & rhs,
+        "==" => (lhs == rhs) as i64,
+        "!=" => (lhs != rhs) as i64,
+        "<" => (lhs < rhs) as i64,
+        ">" => (lhs > rhs) as i64,
+        "<=" => (lhs <= rhs) as i64,
+        ">=" => (lhs >= rhs) as i64,
+        "<<" => lhs.wrapping_shl(rhs as u32),
+        ">>" => lhs.wrapping_shr(rhs as u32),
+        "+" => lhs.wrapping_add(rhs),
+        "-" => lhs.wrapping_sub(rhs),
+        "*" => lhs.wrapping_mul(rhs),
+        "/" | "%" if rhs == 0 => {
+            if eval {
+                return Err(Error::ArithmeticError("division by 0".to_string(), at));
+            }
+            0
+        },
+        "/" => lhs.wrapping_div(rhs),
+        "%" => lhs.wrapping_rem(rhs),
+        _ => unreachable!("unknown binary operator {}", op),
+    })
+}
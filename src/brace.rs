@@ -0,0 +1,197 @@
+use crate::error::Error;
+
+/// Brace expansion as in bash: `a{b,c}d` gives `abd acd`, `{1..3}` gives `1 2 3`.
+///
+/// Supports nested braces, comma lists, numeric and character ranges with an
+/// optional step (`{1..10..2}`, `{a..e}`) and zero-padding (`{01..10}`).
+/// Braces inside `${...}`, `$(...)`, quotes or after a backslash are not expanded,
+/// and braces that are not a valid expansion (like `{a}`) are kept as-is.
+///
+/// Fails if the expansion would give more than `MAX_WORDS` words.
+pub(crate) fn expand(input: &str) -> Result<Vec<String>, Error> {
+    expand_at(input, 0)
+}
+
+/// Most words a brace expansion may produce, so that `{1..100000000000}` is an
+/// error rather than running out of memory.
+const MAX_WORDS: usize = 100_000;
+
+/// Expand `input`, which starts at position `pos` of the whole input.
+fn expand_at(input: &str, pos: usize) -> Result<Vec<String>, Error> {
+    let Some((open, close, items)) = find_expansion(input, pos)? else {
+        return Ok(vec![input.to_string()]);
+    };
+
+    let prefix = &input[..open];
+    let suffixes = expand_at(&input[close + 1..], pos + close + 1)?;
+    let mut words = Vec::new();
+    for (offset, item) in items {
+        for middle in expand_at(&item, pos + offset)? {
+            for suffix in &suffixes {
+                if words.len() == MAX_WORDS {
+                    return Err(too_many_words(pos + open));
+                }
+                words.push(format!("{}{}{}", prefix, middle, suffix));
+            }
+        }
+    }
+    Ok(words)
+}
+
+fn too_many_words(pos: usize) -> Error {
+    Error::SyntaxError(format!("brace expansion gives more than {} words", MAX_WORDS), pos)
+}
+
+/// Find the first valid brace expression. Returns the positions of its braces
+/// and its items with their offsets, which are either comma alternatives or the
+/// terms of a sequence.
+#[allow(clippy::type_complexity)]
+fn find_expansion(s: &str, pos: usize) -> Result<Option<(usize, usize, Vec<(usize, String)>)>, Error> {
+    let mut i = 0;
+    while i < s.len() {
+        if s[i..].starts_with('{') {
+            if let Some((close, commas)) = find_close(s, i) {
+                let body = &s[i + 1..close];
+                if !commas.is_empty() {
+                    let mut start = i + 1;
+                    let mut items = Vec::new();
+                    for comma in commas.into_iter().chain(std::iter::once(close)) {
+                        items.push((start, s[start..comma].to_string()));
+                        start = comma + 1;
+                    }
+                    return Ok(Some((i, close, items)));
+                }
+                if let Some(terms) = sequence(body, pos + i)? {
+                    return Ok(Some((i, close, terms.into_iter().map(|term| (i + 1, term)).collect())));
+                }
+            }
+        }
+        i = skip(s, i);
+    }
+    Ok(None)
+}
+
+/// Find the `}` matching the `{` at `open`, and the top-level commas in between.
+fn find_close(s: &str, open: usize) -> Option<(usize, Vec<usize>)> {
+    let mut depth = 0;
+    let mut commas = Vec::new();
+    let mut i = open;
+    while i < s.len() {
+        match s.as_bytes()[i] {
+            b'{' => depth += 1,
+            b'}' => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some((i, commas));
+                }
+            },
+            b',' if depth == 1 => commas.push(i),
+            _ => {}
+        }
+        i = skip(s, i);
+    }
+    None
+}
+
+/// Index after the syntactic unit starting at `i`: a character, an escape, a
+/// quoted string, a backtick command or a `${...}` / `$(...)` expansion.
+/// Unterminated units count as a single character.
+fn skip(s: &str, i: usize) -> usize {
+    let rest = &s[i..];
+    let one = i + rest.chars().next().map_or(1, char::len_utf8);
+
+    let closing = |open_len: usize, close: char| {
+        let mut chars = rest[open_len..].char_indices();
+        while let Some((j, c)) = chars.next() {
+            if c == '\\' && close != '\'' {
+                chars.next();
+            } else if c == close {
+                return Some(i + open_len + j + 1);
+            }
+        }
+        None
+    };
+
+    let end = if let Some(escaped) = rest.strip_prefix('\\') {
+        escaped.chars().next().map(|c| i + 1 + c.len_utf8())
+    } else if rest.starts_with("$'") {
+        closing(2, '\'')
+    } else if rest.starts_with('\'') {
+        closing(1, '\'')
+    } else if rest.starts_with('"') {
+        closing(1, '"')
+    } else if rest.starts_with('`') {
+        closing(1, '`')
+    } else if rest.starts_with("${") || rest.starts_with("$(") {
+        let (open, close) = if rest.starts_with("${") { (b'{', b'}') } else { (b'(', b')') };
+        let mut depth = 0;
+        rest.bytes().enumerate().skip(1).find_map(|(j, b)| {
+            if b == open {
+                depth += 1;
+            } else if b == close {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(i + j + 1);
+                }
+            }
+            None
+        })
+    } else {
+        None
+    };
+    end.unwrap_or(one)
+}
+
+/// Expand a sequence expression `x..y` or `x..y..step`, where `x` and `y` are
+/// both integers or both letters of the same case. `pos` is the position of
+/// the expression, used for errors.
+fn sequence(body: &str, pos: usize) -> Result<Option<Vec<String>>, Error> {
+    let parts: Vec<&str> = body.split("..").collect();
+    let (start, end, step) = match parts[..] {
+        [start, end] => (start, end, None),
+        [start, end, step] => match step.parse::<i64>() {
+            Ok(step) => (start, end, Some(step)),
+            Err(_) => return Ok(None),
+        },
+        _ => return Ok(None),
+    };
+    // The direction comes from the endpoints, only the size of the step counts
+    let step = step.map_or(1, |s| s.unsigned_abs().max(1));
+
+    if let (Ok(a), Ok(b)) = (start.parse::<i64>(), end.parse::<i64>()) {
+        // A leading zero on either endpoint pads every term to the same width
+        let padded = |n: &str| n.trim_start_matches('-').len() > 1 && n.trim_start_matches('-').starts_with('0');
+        let width = if padded(start) || padded(end) { start.len().max(end.len()) } else { 0 };
+        let terms = range(a, b, step, pos)?.map(|n| {
+            if n < 0 {
+                format!("-{:0>w$}", n.unsigned_abs(), w = width.saturating_sub(1))
+            } else {
+                format!("{:0>w$}", n, w = width)
+            }
+        });
+        return Ok(Some(terms.collect()));
+    }
+
+    let mut start_chars = start.chars();
+    let mut end_chars = end.chars();
+    match (start_chars.next(), start_chars.next(), end_chars.next(), end_chars.next()) {
+        // Mixed case like {Z..a} would include the punctuation between the cases
+        (Some(a), None, Some(b), None)
+            if (a.is_ascii_lowercase() && b.is_ascii_lowercase()) || (a.is_ascii_uppercase() && b.is_ascii_uppercase()) =>
+        {
+            let terms = range(a as i64, b as i64, step, pos)?;
+            Ok(Some(terms.map(|c| (c as u8 as char).to_string()).collect()))
+        },
+        _ => Ok(None),
+    }
+}
+
+/// Terms from `a` to `b` inclusive, counting up or down by `step`.
+fn range(a: i64, b: i64, step: u64, pos: usize) -> Result<impl Iterator<Item = i64>, Error> {
+    let count = (a.abs_diff(b) / step)
+        .checked_add(1)
+        .filter(|&count| count <= MAX_WORDS as u64)
+        .ok_or_else(|| too_many_words(pos))?;
+    let step = if a <= b { step as i64 } else { -(step as i64) };
+    Ok((0..count).map(move |k| a.wrapping_add((k as i64).wrapping_mul(step))))
+}
@@ -12,8 +12,41 @@ pub struct FeatureConfig {
     pub alternates: bool,
     /// Enable conditional values (${VAR:+value})
     pub conditionals: bool,
+    /// Enable assign-default values (${VAR:=default})
+    pub assignments: bool,
+    /// Enable error expansions (${VAR:?message})
+    pub errors: bool,
+    /// Enable string length (${#VAR})
+    pub lengths: bool,
+    /// Enable substrings (${VAR:offset:length})
+    pub substrings: bool,
+    /// Enable pattern removal (${VAR#pattern}, ${VAR%pattern})
+    pub pattern_removal: bool,
+    /// Enable pattern replacement (${VAR/pattern/replacement})
+    pub pattern_replacement: bool,
+    /// Enable uppercasing the first character (${VAR^})
+    pub upper_first: bool,
+    /// Enable uppercasing all characters (${VAR^^})
+    pub upper_all: bool,
+    /// Enable lowercasing the first character (${VAR,})
+    pub lower_first: bool,
+    /// Enable lowercasing all characters (${VAR,,})
+    pub lower_all: bool,
+    /// Enable indirect expansion (${!VAR}) and name listing (${!PREFIX*})
+    pub indirection: bool,
+    /// Enable arithmetic expansion ($((expr)))
+    pub arithmetic: bool,
+    /// Enable ANSI-C quoting ($'...')
+    pub ansi_c_quotes: bool,
+    /// Enable tilde expansion (~, ~/path, ~+, ~-, ~user) at word starts and after ':'.
+    /// Disabled by default, so a leading ~ is kept as literal text.
+    pub tilde: bool,
     /// Enable escape sequences
     pub escapes: bool,
+    /// Enable shell-style double quotes ("..."): the quotes are removed, variables
+    /// expand inside and only \$ \` \" \\ are escapes. Unclosed quotes are errors.
+    /// Disabled by default, so quote characters are kept as literal text.
+    pub double_quotes: bool,
     /// Enable command substitution ($(cmd))
     pub commands: bool,
     /// Enable backtick command substitution (`cmd`)
@@ -27,20 +60,56 @@ impl Default for FeatureConfig {
             defaults: true,
             alternates: true,
             conditionals: true,
+            assignments: true,
+            errors: true,
+            lengths: true,
+            substrings: true,
+            pattern_removal: true,
+            pattern_replacement: true,
+            upper_first: true,
+            upper_all: true,
+            lower_first: true,
+            lower_all: true,
+            indirection: true,
+            arithmetic: true,
+            ansi_c_quotes: true,
+            tilde: false,
             escapes: true,
+            double_quotes: false,
             commands: true,
             backtick_commands: true,
         }
     }
 }
 
+/// Dialect used for backslash escape sequences in literal text
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum EscapeStyle {
+    /// Bash-like: `\n \t \xHH \uHHHH \u{...} \0 \a \b \e \f \v`, unknown `\c` becomes `c`.
+    /// `\x` and `\u` without hex digits are kept as-is
+    #[default]
+    Bash,
+    /// JSON string escapes, including `\uHHHH` surrogate pairs; anything else is an error
+    Json,
+    /// Rust string literal escapes (`\x00`-`\x7F`, `\u{...}`); anything else is an error
+    Rust,
+    /// Backslashes in literal text are kept as-is
+    None,
+}
+
 #[derive(Debug, Clone)]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Config {
     /// Maximum recursion depth for variable expansion
     pub max_depth: usize,
-    /// Enable strict mode (fail on empty values if using :?)
+    /// Enable strict mode (treat empty values as unset in `${VAR?message}`, like `:?`)
     pub strict_unsets: bool,
+    /// Escape sequence dialect
+    pub escape_style: EscapeStyle,
+    /// Remove the delimiters of single-quoted text ('...') and keep the text inside
+    /// verbatim, without decoding escapes, like shells and dotenv do
+    pub strip_single_quotes: bool,
     /// Feature flags
     pub features: FeatureConfig,
 }
@@ -50,6 +119,8 @@ impl Default for Config {
         Self {
             max_depth: 10,
             strict_unsets: false,
+            escape_style: EscapeStyle::default(),
+            strip_single_quotes: false,
             features: FeatureConfig::default(),
         }
     }
@@ -1,20 +1,95 @@
+use std::borrow::Cow;
 use std::collections::HashMap;
+use std::sync::Arc;
 
 /// A trait for providing variable values during interpolation.
 pub trait VariableProvider {
-    /// Retrieve the value of a variable by name.
-    fn get_value(&self, key: &str) -> Option<&str>;
+    /// Retrieve the value of a variable by name. Stored values are borrowed,
+    /// while providers that read them on demand can return owned strings.
+    fn get_value(&self, key: &str) -> Option<Cow<'_, str>>;
+
+    /// Retrieve the value of a variable, or fail for reasons other than it
+    /// being unset, such as an unreadable file or a locked keyring. The error
+    /// stops interpolation as `Error::ProviderError`.
+    ///
+    /// The default calls `get_value`, which cannot fail. Fallible providers
+    /// implement this and have `get_value` return `None` on errors.
+    fn try_get_value(&self, key: &str) -> Result<Option<Cow<'_, str>>, Box<dyn std::error::Error + Send + Sync>> {
+        Ok(self.get_value(key))
+    }
+
+    /// List the names of all variables, if the provider supports enumeration.
+    /// Used by `${!PREFIX*}`; the default returns `None`.
+    fn keys(&self) -> Option<Vec<Cow<'_, str>>> {
+        None
+    }
+
+    /// Elements of an array variable, for `${ARR[i]}` and `${ARR[@]}`. The
+    /// default returns `None`, so that every variable is a scalar.
+    ///
+    /// Only called when `get_value` has no value for `$ARR`, or for subscripts.
+    fn get_array(&self, key: &str) -> Option<Vec<Cow<'_, str>>> {
+        let _ = key;
+        None
+    }
+
+    /// Value of a special parameter: `$` for `$$`, `?`, `!` or `-`. Hosts decide
+    /// what these mean, e.g. their own process id for `$$`.
+    ///
+    /// The default looks `name` up with `get_value`, so a map can provide them
+    /// with keys like `"?"`. Parameters without a value are left as written.
+    fn special_value(&self, name: &str) -> Option<String> {
+        self.get_value(name).map(Cow::into_owned)
+    }
 }
 
+/// Providers behind a reference or smart pointer, so that they can be shared
+/// between `Germi` instances, or be a `Box<dyn VariableProvider>`.
+macro_rules! impl_provider_for_pointer {
+    ($($pointer:ty),*) => {$(
+        impl<P: VariableProvider + ?Sized> VariableProvider for $pointer {
+            fn get_value(&self, key: &str) -> Option<Cow<'_, str>> {
+                (**self).get_value(key)
+            }
+
+            fn try_get_value(&self, key: &str) -> Result<Option<Cow<'_, str>>, Box<dyn std::error::Error + Send + Sync>> {
+                (**self).try_get_value(key)
+            }
+
+            fn keys(&self) -> Option<Vec<Cow<'_, str>>> {
+                (**self).keys()
+            }
+
+            fn get_array(&self, key: &str) -> Option<Vec<Cow<'_, str>>> {
+                (**self).get_array(key)
+            }
+
+            fn special_value(&self, name: &str) -> Option<String> {
+                (**self).special_value(name)
+            }
+        }
+    )*};
+}
+
+impl_provider_for_pointer!(&P, Box<P>, Arc<P>);
+
 impl VariableProvider for HashMap<String, String> {
-    fn get_value(&self, key: &str) -> Option<&str> {
-        self.get(key).map(|s| s.as_str())
+    fn get_value(&self, key: &str) -> Option<Cow<'_, str>> {
+        self.get(key).map(|s| Cow::Borrowed(s.as_str()))
+    }
+
+    fn keys(&self) -> Option<Vec<Cow<'_, str>>> {
+        Some(HashMap::keys(self).map(|k| Cow::Borrowed(k.as_str())).collect())
     }
 }
 
 impl VariableProvider for HashMap<&str, &str> {
-    fn get_value(&self, key: &str) -> Option<&str> {
-        self.get(key).copied()
+    fn get_value(&self, key: &str) -> Option<Cow<'_, str>> {
+        self.get(key).map(|s| Cow::Borrowed(*s))
+    }
+
+    fn keys(&self) -> Option<Vec<Cow<'_, str>>> {
+        Some(HashMap::keys(self).map(|k| Cow::Borrowed(*k)).collect())
     }
 }
 
@@ -22,22 +97,79 @@ impl VariableProvider for HashMap<&str, &str> {
 #[derive(Debug, Clone, Default)]
 pub struct SimpleContext {
     vars: HashMap<String, String>,
+    arrays: HashMap<String, Vec<String>>,
 }
 
 impl SimpleContext {
     pub fn new() -> Self {
         Self {
             vars: HashMap::new(),
+            arrays: HashMap::new(),
         }
     }
 
+    /// Insert a scalar variable, replacing any array of the same name.
     pub fn insert(&mut self, key: impl Into<String>, value: impl Into<String>) {
-        self.vars.insert(key.into(), value.into());
+        let key = key.into();
+        self.arrays.remove(&key);
+        self.vars.insert(key, value.into());
+    }
+
+    /// Insert an array variable, replacing any scalar of the same name.
+    pub fn insert_array<I>(&mut self, key: impl Into<String>, values: I)
+    where
+        I: IntoIterator,
+        I::Item: Into<String>,
+    {
+        let key = key.into();
+        self.vars.remove(&key);
+        self.arrays.insert(key, values.into_iter().map(Into::into).collect());
     }
 }
 
 impl VariableProvider for SimpleContext {
-    fn get_value(&self, key: &str) -> Option<&str> {
-        self.vars.get(key).map(|s| s.as_str())
+    fn get_value(&self, key: &str) -> Option<Cow<'_, str>> {
+        self.vars.get(key).map(|s| Cow::Borrowed(s.as_str()))
+    }
+
+    fn keys(&self) -> Option<Vec<Cow<'_, str>>> {
+        Some(self.vars.keys().chain(self.arrays.keys()).map(|k| Cow::Borrowed(k.as_str())).collect())
+    }
+
+    fn get_array(&self, key: &str) -> Option<Vec<Cow<'_, str>>> {
+        self.arrays.get(key).map(|values| values.iter().map(|v| Cow::Borrowed(v.as_str())).collect())
+    }
+}
+
+/// Resolves home directories for tilde expansion (`~user`).
+///
+/// Used for `~user`, and for `~` when the provider has no `HOME` variable.
+pub trait UserDirResolver {
+    /// Home directory of `user`, or of the current user if `user` is empty.
+    fn home_dir(&self, user: &str) -> Option<String>;
+}
+
+impl<F: Fn(&str) -> Option<String>> UserDirResolver for F {
+    fn home_dir(&self, user: &str) -> Option<String> {
+        self(user)
+    }
+}
+
+/// Resolves home directories from the process environment, and other users'
+/// from `/etc/passwd`.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemUserDirs;
+
+impl UserDirResolver for SystemUserDirs {
+    fn home_dir(&self, user: &str) -> Option<String> {
+        if user.is_empty() {
+            return std::env::var("HOME").or_else(|_| std::env::var("USERPROFILE")).ok();
+        }
+
+        let passwd = std::fs::read_to_string("/etc/passwd").ok()?;
+        passwd.lines().find_map(|line| {
+            let fields: Vec<&str> = line.split(':').collect();
+            (fields.len() > 5 && fields[0] == user).then(|| fields[5].to_string())
+        })
     }
 }
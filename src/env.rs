@@ -0,0 +1,98 @@
+use std::borrow::Cow;
+use std::collections::HashMap;
+
+use crate::context::VariableProvider;
+
+/// Variables from the process environment.
+///
+/// `EnvProvider::snapshot()` copies the environment once, while `EnvProvider::live()`
+/// reads it on every lookup and so sees later `std::env::set_var` calls.
+/// Variables that are not valid UTF-8 are skipped.
+#[derive(Debug, Clone, Default)]
+pub struct EnvProvider {
+    /// Variables copied at construction, or `None` to read the environment on each lookup
+    snapshot: Option<HashMap<String, String>>,
+    /// Only variables starting with this prefix are visible
+    prefix: String,
+    /// Whether variables are named without the prefix, e.g. `PORT` for `APP_PORT`
+    strip_prefix: bool,
+}
+
+impl EnvProvider {
+    /// Copy the current environment. Later changes are not seen.
+    pub fn snapshot() -> Self {
+        let vars = std::env::vars_os()
+            .filter_map(|(key, value)| Some((key.into_string().ok()?, value.into_string().ok()?)))
+            .collect();
+        Self {
+            snapshot: Some(vars),
+            ..Self::default()
+        }
+    }
+
+    /// Read the environment on every lookup.
+    pub fn live() -> Self {
+        Self::default()
+    }
+
+    /// Only expose variables whose names start with `prefix`, keeping their full names.
+    pub fn filter_prefix(mut self, prefix: impl Into<String>) -> Self {
+        self.prefix = prefix.into();
+        self.strip_prefix = false;
+        self
+    }
+
+    /// Only expose variables whose names start with `prefix`, without the prefix,
+    /// so that `APP_PORT` is `${PORT}` with `strip_prefix("APP_")`.
+    pub fn strip_prefix(mut self, prefix: impl Into<String>) -> Self {
+        self.prefix = prefix.into();
+        self.strip_prefix = true;
+        self
+    }
+
+    /// The name under which environment variable `name` is visible, if it is.
+    fn visible_name<'n>(&self, name: Cow<'n, str>) -> Option<Cow<'n, str>> {
+        if !name.starts_with(&self.prefix) {
+            return None;
+        }
+        if !self.strip_prefix {
+            return Some(name);
+        }
+        Some(match name {
+            Cow::Borrowed(name) => Cow::Borrowed(&name[self.prefix.len()..]),
+            Cow::Owned(name) => Cow::Owned(name[self.prefix.len()..].to_string()),
+        })
+    }
+
+    /// The environment variable for `key`, if it is visible.
+    fn env_name<'k>(&self, key: &'k str) -> Option<Cow<'k, str>> {
+        if self.strip_prefix {
+            Some(Cow::Owned(format!("{}{}", self.prefix, key)))
+        } else if key.starts_with(&self.prefix) {
+            Some(Cow::Borrowed(key))
+        } else {
+            None
+        }
+    }
+}
+
+impl VariableProvider for EnvProvider {
+    fn get_value(&self, key: &str) -> Option<Cow<'_, str>> {
+        let name = self.env_name(key)?;
+        match &self.snapshot {
+            Some(vars) => vars.get(name.as_ref()).map(|v| Cow::Borrowed(v.as_str())),
+            None => std::env::var(name.as_ref()).ok().map(Cow::Owned),
+        }
+    }
+
+    fn keys(&self) -> Option<Vec<Cow<'_, str>>> {
+        let names: Vec<Cow<'_, str>> = match &self.snapshot {
+            Some(vars) => vars.keys().map(|k| Cow::Borrowed(k.as_str())).collect(),
+            None => std::env::vars_os()
+                .filter(|(_, value)| value.to_str().is_some())
+                .filter_map(|(key, _)| key.into_string().ok().map(Cow::Owned))
+                .collect(),
+        };
+        Some(names.into_iter().filter_map(|name| self.visible_name(name)).collect())
+    }
+}
@@ -1,13 +1,18 @@
 use std::fmt;
+use std::sync::Arc;
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone)]
 pub enum Error {
     /// Maximum interpolation depth exceeded (potential cycle)
     RecursiveLookup(String),
     /// Variable not found
     MissingVar(String),
+    /// Required variable unset or empty (`${VAR:?message}`): name and message
+    RequiredVar(String, String),
     /// Syntax error at position
     SyntaxError(String, usize),
+    /// Arithmetic expression error at position
+    ArithmeticError(String, usize),
     /// Unterminated variable brace
     UnclosedBrace(usize),
     /// Unterminated quote
@@ -16,20 +21,74 @@ pub enum Error {
     CommandError(String),
     /// IO Error
     IoError(String),
+    /// The provider failed to look up a variable, e.g. because a file could not
+    /// be read. The cause is also available through `std::error::Error::source`.
+    ProviderError {
+        name: String,
+        source: Arc<dyn std::error::Error + Send + Sync>,
+    },
 }
 
+impl Error {
+    /// Move a positioned error to `pos`. Errors in a variable's value or in a
+    /// default word are reported at the expression, as their own positions are
+    /// not in the input.
+    pub(crate) fn at(self, pos: usize) -> Self {
+        match self {
+            Error::SyntaxError(msg, _) => Error::SyntaxError(msg, pos),
+            Error::ArithmeticError(msg, _) => Error::ArithmeticError(msg, pos),
+            Error::UnclosedBrace(_) => Error::UnclosedBrace(pos),
+            Error::UnclosedQuote(_) => Error::UnclosedQuote(pos),
+            other => other,
+        }
+    }
+}
+
+impl PartialEq for Error {
+    fn eq(&self, other: &Self) -> bool {
+        use Error::*;
+        match (self, other) {
+            (RecursiveLookup(a), RecursiveLookup(b))
+            | (MissingVar(a), MissingVar(b))
+            | (CommandError(a), CommandError(b))
+            | (IoError(a), IoError(b)) => a == b,
+            (RequiredVar(a, x), RequiredVar(b, y)) => a == b && x == y,
+            (SyntaxError(a, x), SyntaxError(b, y)) | (ArithmeticError(a, x), ArithmeticError(b, y)) => a == b && x == y,
+            (UnclosedBrace(a), UnclosedBrace(b)) | (UnclosedQuote(a), UnclosedQuote(b)) => a == b,
+            // Sources are not comparable, so compare their messages
+            (ProviderError { name: a, source: x }, ProviderError { name: b, source: y }) => {
+                a == b && x.to_string() == y.to_string()
+            },
+            _ => false,
+        }
+    }
+}
+
+impl Eq for Error {}
+
 impl fmt::Display for Error {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
             Error::RecursiveLookup(ctx) => write!(f, "Maximum interpolation depth exceeded: {}", ctx),
             Error::MissingVar(var) => write!(f, "Variable not found: {}", var),
+            Error::RequiredVar(var, msg) if msg.is_empty() => write!(f, "{}: parameter null or not set", var),
+            Error::RequiredVar(var, msg) => write!(f, "{}: {}", var, msg),
             Error::SyntaxError(msg, pos) => write!(f, "Syntax error at position {}: {}", pos, msg),
+            Error::ArithmeticError(msg, pos) => write!(f, "Arithmetic error at position {}: {}", pos, msg),
             Error::UnclosedBrace(pos) => write!(f, "Unclosed variable brace starting at position {}", pos),
             Error::UnclosedQuote(pos) => write!(f, "Unterminated quote starting at position {}", pos),
             Error::CommandError(msg) => write!(f, "Command execution failed: {}", msg),
             Error::IoError(msg) => write!(f, "IO Error: {}", msg),
+            Error::ProviderError { name, source } => write!(f, "Failed to look up {}: {}", name, source),
         }
     }
 }
 
-impl std::error::Error for Error {}
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::ProviderError { source, .. } => Some(source.as_ref()),
+            _ => None,
+        }
+    }
+}
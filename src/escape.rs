@@ -0,0 +1,260 @@
+use crate::config::EscapeStyle;
+use crate::error::Error;
+
+/// Unescape backslash sequences in literal text according to `style`.
+///
+/// `\$` and `` \` `` never reach this function, the scanner emits them as
+/// escape tokens. `pos` is the position of `s` in the input, used for errors.
+pub(crate) fn unescape_into(buf: &mut String, s: &str, style: EscapeStyle, pos: usize) -> Result<(), Error> {
+    if style == EscapeStyle::None {
+        buf.push_str(s);
+        return Ok(());
+    }
+
+    let mut rest = s;
+    while let Some(backslash) = rest.find('\\') {
+        buf.push_str(&rest[..backslash]);
+        let at = pos + s.len() - rest.len() + backslash;
+        let seq = &rest[backslash + 1..];
+
+        let consumed = match style {
+            EscapeStyle::Bash => unescape_bash(buf, seq, at)?,
+            EscapeStyle::Json => unescape_json(buf, seq, at)?,
+            EscapeStyle::Rust => unescape_rust(buf, seq, at)?,
+            EscapeStyle::None => unreachable!(),
+        };
+        rest = &seq[consumed..];
+    }
+
+    buf.push_str(rest);
+    Ok(())
+}
+
+/// Decode one bash-style sequence (after the backslash), returning the bytes consumed.
+fn unescape_bash(buf: &mut String, seq: &str, at: usize) -> Result<usize, Error> {
+    let Some(c) = seq.chars().next() else {
+        // Trailing backslash
+        buf.push('\\');
+        return Ok(0);
+    };
+
+    match c {
+        'n' => buf.push('\n'),
+        'r' => buf.push('\r'),
+        't' => buf.push('\t'),
+        '0' => buf.push('\0'),
+        'a' => buf.push('\x07'),
+        'b' => buf.push('\x08'),
+        'e' => buf.push('\x1b'),
+        'f' => buf.push('\x0c'),
+        'v' => buf.push('\x0b'),
+        'x' | 'u' => {
+            if c == 'u' {
+                if let Some((value, len)) = braced_unicode(&seq[1..], at)? {
+                    buf.push(code_point(value, at)?);
+                    return Ok(1 + len);
+                }
+            }
+            let (value, len) = read_digits(&seq[1..], 16, if c == 'x' { 2 } else { 4 });
+            if len == 0 {
+                // No digits: keep the sequence literally, like $'...' in bash,
+                // so that paths like C:\users pass through
+                buf.push('\\');
+                buf.push(c);
+                return Ok(1);
+            }
+            buf.push(code_point(value, at)?);
+            return Ok(1 + len);
+        },
+        // Unknown escape: bash behavior is \c -> c
+        _ => buf.push(c),
+    }
+    Ok(c.len_utf8())
+}
+
+/// Decode one JSON sequence (after the backslash), returning the bytes consumed.
+fn unescape_json(buf: &mut String, seq: &str, at: usize) -> Result<usize, Error> {
+    let c = match seq.chars().next() {
+        Some(c @ ('"' | '\\' | '/')) => c,
+        Some('b') => '\x08',
+        Some('f') => '\x0c',
+        Some('n') => '\n',
+        Some('r') => '\r',
+        Some('t') => '\t',
+        Some('u') => {
+            let high = json_unit(&seq[1..], at)?;
+            if !(0xD800..0xDC00).contains(&high) {
+                buf.push(code_point(high, at)?);
+                return Ok(5);
+            }
+            // High surrogate: must be followed by a low surrogate
+            let low = seq[5..]
+                .strip_prefix("\\u")
+                .map(|s| json_unit(s, at + 6))
+                .transpose()?
+                .filter(|low| (0xDC00..0xE000).contains(low))
+                .ok_or_else(|| malformed("\\u", "unpaired surrogate", at))?;
+            buf.push(code_point(0x10000 + ((high - 0xD800) << 10) + (low - 0xDC00), at)?);
+            return Ok(11);
+        },
+        Some(other) => return Err(malformed(&format!("\\{}", other), "not a JSON escape", at)),
+        None => return Err(malformed("\\", "trailing backslash", at)),
+    };
+    buf.push(c);
+    Ok(1)
+}
+
+/// Read the four hex digits of a JSON `\uHHHH` escape.
+fn json_unit(s: &str, at: usize) -> Result<u32, Error> {
+    match read_digits(s, 16, 4) {
+        (value, 4) => Ok(value),
+        _ => Err(malformed("\\u", "expected 4 hex digits", at)),
+    }
+}
+
+/// Decode one Rust sequence (after the backslash), returning the bytes consumed.
+fn unescape_rust(buf: &mut String, seq: &str, at: usize) -> Result<usize, Error> {
+    let c = match seq.chars().next() {
+        Some(c @ ('"' | '\'' | '\\')) => c,
+        Some('n') => '\n',
+        Some('r') => '\r',
+        Some('t') => '\t',
+        Some('0') => '\0',
+        Some('x') => {
+            return match read_digits(&seq[1..], 16, 2) {
+                (value @ 0..=0x7F, 2) => {
+                    buf.push(value as u8 as char);
+                    Ok(3)
+                },
+                (_, 2) => Err(malformed("\\x", "value out of range, must be at most \\x7F", at)),
+                _ => Err(malformed("\\x", "expected 2 hex digits", at)),
+            };
+        },
+        Some('u') => {
+            let (value, len) = braced_unicode(&seq[1..], at)?
+                .ok_or_else(|| malformed("\\u", "expected {...}", at))?;
+            buf.push(code_point(value, at)?);
+            return Ok(1 + len);
+        },
+        Some(other) => return Err(malformed(&format!("\\{}", other), "not a Rust escape", at)),
+        None => return Err(malformed("\\", "trailing backslash", at)),
+    };
+    buf.push(c);
+    Ok(c.len_utf8())
+}
+
+/// Parse `{H...}` with one to six hex digits, as in `\u{1F600}`.
+/// Returns `None` if `s` does not start with `{`.
+fn braced_unicode(s: &str, at: usize) -> Result<Option<(u32, usize)>, Error> {
+    let Some(body) = s.strip_prefix('{') else {
+        return Ok(None);
+    };
+    let (value, len) = read_digits(body, 16, 6);
+    if len == 0 || !body[len..].starts_with('}') {
+        return Err(malformed("\\u{", "expected 1 to 6 hex digits and '}'", at));
+    }
+    Ok(Some((value, len + 2)))
+}
+
+fn malformed(seq: &str, reason: &str, at: usize) -> Error {
+    Error::SyntaxError(format!("malformed escape sequence {}: {}", seq, reason), at)
+}
+
+/// Decode the body of an ANSI-C quoted string `$'...'`.
+///
+/// Supports `\a \b \e \E \f \n \r \t \v \\ \' \" \?`, octal `\0NNN` and `\NNN`,
+/// `\xHH`, `\uHHHH`, `\UHHHHHHHH` and control characters `\cX`. Byte values are
+/// taken as Unicode code points. Unknown escapes are kept as-is, like bash does.
+/// `pos` is the position of the body in the input, used for errors.
+pub(crate) fn decode_ansi_c(s: &str, pos: usize) -> Result<String, Error> {
+    let mut buf = String::with_capacity(s.len());
+    let mut rest = s;
+
+    while let Some(backslash) = rest.find('\\') {
+        buf.push_str(&rest[..backslash]);
+        let at = pos + s.len() - rest.len() + backslash;
+        let seq = &rest[backslash + 1..];
+
+        let Some(c) = seq.chars().next() else {
+            // Trailing backslash
+            buf.push('\\');
+            return Ok(buf);
+        };
+        let mut consumed = c.len_utf8();
+
+        match c {
+            'a' => buf.push('\x07'),
+            'b' => buf.push('\x08'),
+            'e' | 'E' => buf.push('\x1b'),
+            'f' => buf.push('\x0c'),
+            'n' => buf.push('\n'),
+            'r' => buf.push('\r'),
+            't' => buf.push('\t'),
+            'v' => buf.push('\x0b'),
+            '\\' | '\'' | '"' | '?' => buf.push(c),
+            '0'..='7' => {
+                // \0 takes up to three more digits, \N up to two more
+                let max = if c == '0' { 4 } else { 3 };
+                let (value, len) = read_digits(seq, 8, max);
+                buf.push(code_point(value, at)?);
+                consumed = len;
+            },
+            'x' | 'u' | 'U' => {
+                let max = match c {
+                    'x' => 2,
+                    'u' => 4,
+                    _ => 8,
+                };
+                let (value, len) = read_digits(&seq[1..], 16, max);
+                if len == 0 {
+                    // No digits: keep the sequence literally
+                    buf.push('\\');
+                    buf.push(c);
+                } else {
+                    buf.push(code_point(value, at)?);
+                    consumed += len;
+                }
+            },
+            'c' => match seq[1..].chars().next() {
+                Some(x) if x.is_ascii() => {
+                    buf.push(((x.to_ascii_uppercase() as u8) ^ 0x40) as char);
+                    consumed += 1;
+                },
+                _ => buf.push_str("\\c"),
+            },
+            _ => {
+                buf.push('\\');
+                buf.push(c);
+            },
+        }
+
+        rest = &seq[consumed..];
+    }
+
+    buf.push_str(rest);
+    Ok(buf)
+}
+
+/// Read up to `max` digits in `radix` from the start of `s`.
+/// Returns the value and the number of bytes read.
+pub(crate) fn read_digits(s: &str, radix: u32, max: usize) -> (u32, usize) {
+    let mut value = 0u32;
+    let mut len = 0;
+    for c in s.chars().take(max) {
+        match c.to_digit(radix) {
+            Some(d) => {
+                value = value.saturating_mul(radix).saturating_add(d);
+                len += 1;
+            },
+            None => break,
+        }
+    }
+    (value, len)
+}
+
+/// Convert a code point from an escape sequence, rejecting surrogates and
+/// values beyond the Unicode range.
+pub(crate) fn code_point(value: u32, at: usize) -> Result<char, Error> {
+    char::from_u32(value)
+        .ok_or_else(|| Error::SyntaxError(format!("invalid code point U+{:X} in escape sequence", value), at))
+}
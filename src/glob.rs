@@ -0,0 +1,229 @@
+use regex::Regex;
+
+use crate::error::Error;
+use crate::scanner::ReplaceMode;
+
+/// A shell glob pattern (`*`, `?`, `[...]`) compiled for matching and searching.
+#[derive(Debug)]
+pub(crate) enum GlobPattern {
+    /// Pattern without wildcards, matched by plain comparison
+    Literal(String),
+    /// Pattern translated to regex, both anchored for whole-string matches
+    /// and unanchored for searching
+    Regex { anchored: Regex, search: Regex },
+}
+
+impl GlobPattern {
+    /// Compile a glob pattern. `pos` is reported on error.
+    pub(crate) fn new(glob: &str, pos: usize) -> Result<Self, Error> {
+        if !glob.contains(['*', '?', '[', '\\']) {
+            return Ok(GlobPattern::Literal(glob.to_string()));
+        }
+
+        let translated = translate(glob);
+        let compile = |re: String| {
+            Regex::new(&re).map_err(|e| Error::SyntaxError(format!("invalid pattern {:?}: {}", glob, e), pos))
+        };
+        Ok(GlobPattern::Regex {
+            anchored: compile(format!("(?s)^(?:{})$", translated))?,
+            search: compile(format!("(?s){}", translated))?,
+        })
+    }
+
+    /// Check whether the pattern matches all of `s`.
+    pub(crate) fn matches(&self, s: &str) -> bool {
+        match self {
+            GlobPattern::Literal(lit) => lit == s,
+            GlobPattern::Regex { anchored, .. } => anchored.is_match(s),
+        }
+    }
+
+    /// Remove the shortest (or longest) matching prefix of `value`.
+    pub(crate) fn remove_prefix<'v>(&self, value: &'v str, longest: bool) -> &'v str {
+        self.prefix_end(value, longest).map_or(value, |i| &value[i..])
+    }
+
+    /// Remove the shortest (or longest) matching suffix of `value`.
+    pub(crate) fn remove_suffix<'v>(&self, value: &'v str, longest: bool) -> &'v str {
+        self.suffix_start(value, longest).map_or(value, |i| &value[..i])
+    }
+
+    /// Replace matches of the pattern in `value`. Matches are the longest possible
+    /// at the leftmost position, as in bash.
+    pub(crate) fn replace(&self, value: &str, replacement: &str, mode: ReplaceMode) -> String {
+        match mode {
+            ReplaceMode::Prefix => match self.prefix_end(value, true) {
+                Some(i) => format!("{}{}", replacement, &value[i..]),
+                None => value.to_string(),
+            },
+            ReplaceMode::Suffix => match self.suffix_start(value, true) {
+                Some(i) => format!("{}{}", &value[..i], replacement),
+                None => value.to_string(),
+            },
+            ReplaceMode::First | ReplaceMode::All => match self {
+                GlobPattern::Literal(lit) if lit.is_empty() => value.to_string(),
+                GlobPattern::Literal(lit) if mode == ReplaceMode::First => value.replacen(lit.as_str(), replacement, 1),
+                GlobPattern::Literal(lit) => value.replace(lit.as_str(), replacement),
+                GlobPattern::Regex { anchored, search } => {
+                    let mut result = String::with_capacity(value.len());
+                    let mut pos = 0;
+                    while let Some(m) = search.find_at(value, pos) {
+                        // Extend to the longest match starting here
+                        let end = char_boundaries(&value[m.start()..])
+                            .into_iter()
+                            .rev()
+                            .map(|i| m.start() + i)
+                            .find(|&end| anchored.is_match(&value[m.start()..end]))
+                            .unwrap_or(m.end());
+                        result.push_str(&value[pos..m.start()]);
+                        if end == m.start() {
+                            // Null matches are not replaced
+                            match value[end..].chars().next() {
+                                Some(c) => {
+                                    result.push(c);
+                                    pos = end + c.len_utf8();
+                                    continue;
+                                },
+                                None => {
+                                    pos = end;
+                                    break;
+                                },
+                            }
+                        }
+                        result.push_str(replacement);
+                        pos = end;
+                        if mode == ReplaceMode::First {
+                            break;
+                        }
+                    }
+                    result.push_str(&value[pos..]);
+                    result
+                },
+            },
+        }
+    }
+
+    /// End of the shortest (or longest) prefix of `value` matching the pattern.
+    fn prefix_end(&self, value: &str, longest: bool) -> Option<usize> {
+        if let GlobPattern::Literal(lit) = self {
+            return value.starts_with(lit.as_str()).then_some(lit.len());
+        }
+
+        let mut bounds = char_boundaries(value);
+        if longest {
+            bounds.reverse();
+        }
+        bounds.into_iter().find(|&i| self.matches(&value[..i]))
+    }
+
+    /// Start of the shortest (or longest) suffix of `value` matching the pattern.
+    fn suffix_start(&self, value: &str, longest: bool) -> Option<usize> {
+        if let GlobPattern::Literal(lit) = self {
+            return value.ends_with(lit.as_str()).then(|| value.len() - lit.len());
+        }
+
+        let mut bounds = char_boundaries(value);
+        if !longest {
+            bounds.reverse();
+        }
+        bounds.into_iter().find(|&i| self.matches(&value[i..]))
+    }
+}
+
+/// All char boundaries of `s`, including 0 and `s.len()`, in ascending order.
+fn char_boundaries(s: &str) -> Vec<usize> {
+    s.char_indices().map(|(i, _)| i).chain(std::iter::once(s.len())).collect()
+}
+
+/// Translate glob syntax to (unanchored) regex syntax.
+fn translate(glob: &str) -> String {
+    let mut re = String::with_capacity(glob.len() * 2);
+    let mut chars = glob.char_indices().peekable();
+
+    while let Some((i, c)) = chars.next() {
+        match c {
+            '*' => {
+                // Consecutive stars are equivalent to one
+                while chars.next_if(|&(_, c)| c == '*').is_some() {}
+                re.push_str(".*");
+            },
+            '?' => re.push('.'),
+            '\\' => match chars.next() {
+                Some((_, escaped)) => push_literal(&mut re, escaped),
+                None => push_literal(&mut re, '\\'),
+            },
+            '[' => match translate_class(&glob[i + 1..]) {
+                Some((class, consumed)) => {
+                    re.push_str(&class);
+                    // Skip the class body and closing ']'
+                    let end = i + 1 + consumed;
+                    while chars.next_if(|&(j, _)| j < end).is_some() {}
+                },
+                // Unterminated class: '[' is literal
+                None => push_literal(&mut re, '['),
+            },
+            _ => push_literal(&mut re, c),
+        }
+    }
+
+    re
+}
+
+/// Translate a bracket expression body (after the opening `[`).
+/// Returns the regex class and the number of bytes consumed, including the closing `]`.
+fn translate_class(body: &str) -> Option<(String, usize)> {
+    let mut class = String::from("[");
+    let mut rest = body;
+
+    if let Some(r) = rest.strip_prefix(['!', '^']) {
+        class.push('^');
+        rest = r;
+    }
+
+    let mut first = true;
+    loop {
+        let c = rest.chars().next()?;
+        if c == ']' && !first {
+            class.push(']');
+            let consumed = body.len() - rest.len() + 1;
+            return Some((class, consumed));
+        }
+        first = false;
+
+        // POSIX character classes like [:alpha:] are supported by regex as-is
+        if c == '[' && rest.starts_with("[:") {
+            if let Some(end) = rest[2..].find(":]") {
+                class.push_str(&rest[..end + 4]);
+                rest = &rest[end + 4..];
+                continue;
+            }
+        }
+
+        match c {
+            '\\' => {
+                rest = &rest[1..];
+                let escaped = rest.chars().next()?;
+                push_class_literal(&mut class, escaped);
+                rest = &rest[escaped.len_utf8()..];
+                continue;
+            },
+            // Ranges like a-z pass through
+            '-' => class.push('-'),
+            _ => push_class_literal(&mut class, c),
+        }
+        rest = &rest[c.len_utf8()..];
+    }
+}
+
+fn push_literal(re: &mut String, c: char) {
+    let mut buf = [0u8; 4];
+    re.push_str(&regex::escape(c.encode_utf8(&mut buf)));
+}
+
+fn push_class_literal(class: &mut String, c: char) {
+    // Characters with special meaning inside a regex class
+    if matches!(c, '[' | ']' | '\\' | '^' | '&' | '~' | '-') {
+        class.push('\\');
+    }
+    class.push(c);
+}
@@ -1,9 +1,14 @@
 use std::borrow::Cow;
+use std::cell::RefCell;
 use std::collections::HashMap;
-use crate::context::VariableProvider;
+use crate::argv::{self, Fields};
+use crate::arithmetic;
+use crate::context::{SystemUserDirs, UserDirResolver, VariableProvider};
 use crate::error::Error;
-use crate::scanner::{Scanner, Token};
-use crate::config::Config;
+use crate::escape;
+use crate::glob::GlobPattern;
+use crate::scanner::{self, CaseMode, Operator, Scanner, Token};
+use crate::config::{Config, EscapeStyle};
 
 struct OverlayProvider<'a, P: VariableProvider + ?Sized> {
     base: &'a P,
@@ -11,19 +16,81 @@ struct OverlayProvider<'a, P: VariableProvider + ?Sized> {
 }
 
 impl<'a, P: VariableProvider + ?Sized> VariableProvider for OverlayProvider<'a, P> {
-    fn get_value(&self, key: &str) -> Option<&str> {
-        self.overlay.get(key).map(|s| s.as_str()).or_else(|| self.base.get_value(key))
+    fn get_value(&self, key: &str) -> Option<Cow<'_, str>> {
+        match self.overlay.get(key) {
+            Some(value) => Some(Cow::Borrowed(value)),
+            None => self.base.get_value(key),
+        }
+    }
+
+    fn try_get_value(&self, key: &str) -> Result<Option<Cow<'_, str>>, Box<dyn std::error::Error + Send + Sync>> {
+        match self.overlay.get(key) {
+            Some(value) => Ok(Some(Cow::Borrowed(value))),
+            None => self.base.try_get_value(key),
+        }
+    }
+
+    fn keys(&self) -> Option<Vec<Cow<'_, str>>> {
+        let mut keys = self.base.keys().unwrap_or_default();
+        keys.extend(self.overlay.keys().map(|k| Cow::Borrowed(k.as_str())));
+        Some(keys)
+    }
+
+    fn get_array(&self, key: &str) -> Option<Vec<Cow<'_, str>>> {
+        // A temporary scalar hides an array of the same name
+        if self.overlay.contains_key(key) {
+            return None;
+        }
+        self.base.get_array(key)
+    }
+
+    fn special_value(&self, name: &str) -> Option<String> {
+        self.overlay.get(name).cloned().or_else(|| self.base.special_value(name))
     }
 }
 
+/// The items of `$@` or `${ARR[@]}`, and how many of them come before the elements.
+type List<'a> = (Vec<Cow<'a, str>>, usize);
+
 pub struct Interpolator<'a> {
     context: &'a dyn VariableProvider,
     config: &'a Config,
+    /// Variables assigned by `${VAR:=value}` during interpolation.
+    /// These shadow the context for the rest of the interpolation.
+    assigned: RefCell<HashMap<String, String>>,
+    /// Home directory lookup for `~user`, and for `~` without `HOME`
+    user_dirs: &'a dyn UserDirResolver,
+    /// Positional parameters, `$0` first. Without them `$1` and friends are
+    /// left as-is.
+    args: Option<&'a [&'a str]>,
 }
 
 impl<'a> Interpolator<'a> {
     pub fn new(context: &'a dyn VariableProvider, config: &'a Config) -> Self {
-        Self { context, config }
+        Self {
+            context,
+            config,
+            assigned: RefCell::new(HashMap::new()),
+            user_dirs: &SystemUserDirs,
+            args: None,
+        }
+    }
+
+    /// Set the positional parameters: `args[0]` is `$0`, `args[1]` is `$1` and so on.
+    pub fn with_args(mut self, args: &'a [&'a str]) -> Self {
+        self.args = Some(args);
+        self
+    }
+
+    /// Use `user_dirs` instead of the system lookup for tilde expansion.
+    pub fn with_user_dirs(mut self, user_dirs: &'a dyn UserDirResolver) -> Self {
+        self.user_dirs = user_dirs;
+        self
+    }
+
+    /// Consume the interpolator, returning the variables assigned by `${VAR:=value}`.
+    pub fn into_assignments(self) -> HashMap<String, String> {
+        self.assigned.into_inner()
     }
 
     /// Interpolate a string using variables from the provider, respecting the configuration.
@@ -44,9 +111,78 @@ impl<'a> Interpolator<'a> {
          let temp_interpolator = Interpolator {
              context: &overlay,
              config: self.config,
+             assigned: RefCell::new(self.assigned.take()),
+             user_dirs: self.user_dirs,
+             args: self.args,
          };
 
-         temp_interpolator.resolve(input, 0, false)
+         let result = temp_interpolator.resolve(input, 0, false);
+         self.assigned.replace(temp_interpolator.into_assignments());
+         result
+    }
+
+    /// Interpolate a command line and split it into arguments like the shell.
+    ///
+    /// Quotes are always processed and removed. Unquoted expansions are split
+    /// on the characters of the `IFS` variable (space, tab and newline by default),
+    /// while text in quotes stays in one argument. Commands are not executed.
+    pub fn interpolate_argv(&self, input: &str) -> Result<Vec<String>, Error> {
+        let mut scanner = Scanner::with_quotes(input);
+        let mut fields = Fields::default();
+        let mut in_double_quote = false;
+        // Set when "$@" or "${ARR[@]}" expands to nothing, which makes no argument
+        let mut empty_list = false;
+
+        while let Some((token, range)) = scanner.scan_next()? {
+            match token {
+                Token::Literal(s) if in_double_quote => fields.push_quoted(s),
+                Token::Literal(s) => fields.push_unquoted(s),
+                Token::Quoted(body) => fields.push_quoted(body),
+                Token::DoubleQuote => {
+                    if in_double_quote && !empty_list {
+                        fields.push_quoted("");
+                    }
+                    in_double_quote = !in_double_quote;
+                    empty_list = false;
+                },
+                // $@ and ${ARR[@]} give one argument per element
+                Token::Variable { name, indirect: false, op: None } if (name == "@" && self.args.is_some()) || name.ends_with("[@]") => {
+                    let (items, skip) = self.list(name)?.unwrap_or_default();
+                    empty_list = items.len() == skip;
+                    let ifs = self.lookup("IFS")?;
+                    for (i, item) in items[skip..].iter().enumerate() {
+                        if i > 0 {
+                            fields.end_word();
+                        }
+                        let value = self.resolve_value(item, 0, false).map_err(|e| e.at(range.start))?;
+                        if in_double_quote {
+                            fields.push_quoted(&value);
+                        } else {
+                            fields.push_split(&value, ifs.as_deref().unwrap_or(argv::DEFAULT_IFS));
+                        }
+                    }
+                },
+                Token::Escape(c) => fields.push_quoted(c.encode_utf8(&mut [0; 4])),
+                Token::AnsiCQuoted(body) if self.config.features.ansi_c_quotes => {
+                    fields.push_quoted(&escape::decode_ansi_c(body, range.start + 2)?); // skip "$'"
+                },
+                Token::Command(_) | Token::BacktickCommand(_) | Token::AnsiCQuoted(_) => {
+                    fields.push_quoted(&input[range]);
+                },
+                Token::Variable { .. } | Token::Arithmetic(_) | Token::Tilde(_) => {
+                    let value = self.resolve(&input[range.clone()], 0, false).map_err(|e| e.at(range.start))?;
+                    // Home directories are not split, like in the shell
+                    if in_double_quote || matches!(token, Token::Tilde(_)) {
+                        fields.push_quoted(&value);
+                    } else {
+                        let ifs = self.lookup("IFS")?;
+                        fields.push_split(&value, ifs.as_deref().unwrap_or(argv::DEFAULT_IFS));
+                    }
+                },
+            }
+        }
+
+        Ok(fields.finish())
     }
 
     #[cfg(feature = "async")]
@@ -73,7 +209,7 @@ impl<'a> Interpolator<'a> {
             return Ok(input);
         }
 
-        let mut scanner = Scanner::new(source);
+        let mut scanner = Scanner::with_quote_escapes(source);
         let mut result = String::with_capacity(source.len());
         let mut last_pos = 0;
         let mut modified = false;
@@ -115,7 +251,7 @@ impl<'a> Interpolator<'a> {
             return Ok(input);
         }
 
-        let mut scanner = Scanner::new(source);
+        let mut scanner = Scanner::with_quote_escapes(source);
         let mut result = String::with_capacity(source.len());
         let mut last_pos = 0;
         let mut modified = false;
@@ -198,30 +334,62 @@ impl<'a> Interpolator<'a> {
     /// Internal resolve function.
     /// `preserve_cmd_escapes`: if true, preserve \` and \$ escapes for later processing
     fn resolve<'b>(&self, input: &'b str, depth: usize, preserve_cmd_escapes: bool) -> Result<Cow<'b, str>, Error> {
+        self.resolve_with(input, depth, preserve_cmd_escapes, true)
+    }
+
+    /// Resolve the value of a variable. Quotes and tildes in values are data,
+    /// not syntax, so they are never processed.
+    fn resolve_stored<'b>(&self, value: &'b str, depth: usize, preserve_cmd_escapes: bool) -> Result<Cow<'b, str>, Error> {
+        self.resolve_with(value, depth + 1, preserve_cmd_escapes, false)
+    }
+
+    /// `word`: if false, `input` is a variable value rather than source text.
+    fn resolve_with<'b>(&self, input: &'b str, depth: usize, preserve_cmd_escapes: bool, word: bool) -> Result<Cow<'b, str>, Error> {
         if depth > self.config.max_depth {
             return Err(Error::RecursiveLookup(input.to_string()));
         }
 
-        let mut scanner = Scanner::new(input);
+        let mut scanner = if word && self.config.features.double_quotes {
+            Scanner::with_quotes(input)
+        } else {
+            Scanner::new(input)
+        };
         let mut result: Option<String> = None;
         let mut last_pos = 0;
+        let mut in_double_quote = false;
 
         while let Some((token, range)) = scanner.scan_next()? {
+            // $'...' is plain text when ANSI-C quoting is disabled, and so is
+            // a tilde outside of source text
+            let token = match token {
+                Token::AnsiCQuoted(_) if !self.config.features.ansi_c_quotes => Token::Literal(&input[range.clone()]),
+                Token::Tilde(_) if !(word && self.config.features.tilde) => Token::Literal(&input[range.clone()]),
+                token => token,
+            };
+
             // If it's the first modification, initialize result
             if result.is_none() {
                  match &token {
                      Token::Literal(_) => {
                          // No change yet
                      },
-                     Token::Variable { .. } => {
+                     Token::Variable { .. } | Token::AnsiCQuoted(_) | Token::Tilde(_) | Token::DoubleQuote => {
                          // Will change
                          let mut s = String::with_capacity(input.len() + 32);
                          s.push_str(&input[0..range.start]);
                          result = Some(s);
                      },
-                     Token::Escape(_) => {
+                     Token::Escape(c) => {
                          // Only causes change if not preserving
-                         if !preserve_cmd_escapes {
+                         if !preserve_cmd_escapes || !is_cmd_escape(*c) {
+                             let mut s = String::with_capacity(input.len() + 32);
+                             s.push_str(&input[0..range.start]);
+                             result = Some(s);
+                         }
+                     },
+                     Token::Quoted(_) => {
+                         // Removing the quotes is a change
+                         if self.config.strip_single_quotes {
                              let mut s = String::with_capacity(input.len() + 32);
                              s.push_str(&input[0..range.start]);
                              result = Some(s);
@@ -230,6 +398,14 @@ impl<'a> Interpolator<'a> {
                      Token::Command(_) | Token::BacktickCommand(_) => {
                          // In sync interpolate, we treat commands as literals
                          // No change needed.
+                     },
+                     Token::Arithmetic(_) => {
+                         // Left as-is when arithmetic is disabled
+                         if self.config.features.arithmetic {
+                             let mut s = String::with_capacity(input.len() + 32);
+                             s.push_str(&input[0..range.start]);
+                             result = Some(s);
+                         }
                      }
                  }
             }
@@ -238,15 +414,49 @@ impl<'a> Interpolator<'a> {
                  // Append literal or resolved value
                  match token {
                      Token::Literal(s) => {
-                         if self.config.features.escapes && s.contains('\\') {
-                             Self::unescape_into(res, s);
+                         // Backslashes in double quotes are literal unless
+                         // the scanner emitted them as escapes
+                         if !in_double_quote && self.escapes_enabled() && s.contains('\\') {
+                             self.unescape_into(res, s, range.start)?;
+                         } else if in_double_quote && preserve_cmd_escapes {
+                             // Without the double quotes a `'` would start a
+                             // quoted block in the command pass
+                             push_protected(res, s);
                          } else {
                              res.push_str(s);
                          }
                      },
-                     Token::Variable { name, default, strict, conditional } => {
-                         if self.config.features.variables {
-                             let val = self.resolve_variable(name, default, strict, conditional, depth, preserve_cmd_escapes)?;
+                     Token::Quoted(body) => {
+                         if self.config.strip_single_quotes {
+                             // The body is verbatim, like in shells and dotenv files
+                             if preserve_cmd_escapes {
+                                 // Without its quotes the text is no longer protected
+                                 // from the command pass
+                                 push_protected(res, body);
+                             } else {
+                                 res.push_str(body);
+                             }
+                         } else {
+                             let text = &input[range.clone()];
+                             if self.escapes_enabled() && text.contains('\\') {
+                                 self.unescape_into(res, text, range.start)?;
+                             } else {
+                                 res.push_str(text);
+                             }
+                         }
+                     },
+                     Token::Variable { name, indirect, op } => {
+                         // Keep `$1` as written without positional parameters, and
+                         // `$$` without a value, so that text like "$5" is not changed
+                         let unbound = !indirect
+                             && !input[range.start..].starts_with("${")
+                             && ((self.args.is_none() && scanner::is_positional(name))
+                                 || (scanner::is_special(name) && self.lookup(name)?.is_none()));
+                         if self.config.features.variables && !unbound {
+                             let name = self.resolve_name(name, indirect, depth)?;
+                             let val = self
+                                 .resolve_variable(&name, op.as_ref(), range.start, depth, preserve_cmd_escapes)
+                                 .map_err(|e| e.at(range.start))?;
                              res.push_str(&val);
                          } else {
                              res.push_str(&input[range.clone()]);
@@ -256,8 +466,36 @@ impl<'a> Interpolator<'a> {
                          // In sync mode, commands are treated as literals
                          res.push_str(&input[range.clone()]);
                      },
-                     Token::Escape(c) => {
+                     Token::Arithmetic(expr) => {
+                         if self.config.features.arithmetic {
+                             // Parameters in the expression are expanded before evaluation
+                             let expr = self.resolve(expr, depth + 1, false)?;
+                             let value = self.evaluate_arithmetic(&expr, range.start + 3, depth)?; // skip '$(('
+                             res.push_str(&value.to_string());
+                         } else {
+                             res.push_str(&input[range.clone()]);
+                         }
+                     },
+                     Token::AnsiCQuoted(body) => {
+                         let decoded = escape::decode_ansi_c(body, range.start + 2)?; // skip "$'"
                          if preserve_cmd_escapes {
+                             // Decoded text is literal, so protect it from the command pass
+                             push_protected(res, &decoded);
+                         } else {
+                             res.push_str(&decoded);
+                         }
+                     },
+                     Token::Tilde(user) => match self.expand_tilde(user, depth, preserve_cmd_escapes)? {
+                         Some(dir) => res.push_str(&dir),
+                         // Unknown directories are left as-is
+                         None => res.push_str(&input[range.clone()]),
+                     },
+                     Token::DoubleQuote => {
+                         // Quotes are removed
+                         in_double_quote = !in_double_quote;
+                     },
+                     Token::Escape(c) => {
+                         if preserve_cmd_escapes && is_cmd_escape(c) {
                              // Keep original escape sequence for async pass
                              res.push_str(&input[range.clone()]);
                          } else {
@@ -268,12 +506,13 @@ impl<'a> Interpolator<'a> {
                  }
             } else {
                 // We are still borrowed. Check if we need to switch due to escapes in Literal?
-                if let Token::Literal(s) = token {
-                     if self.config.features.escapes && s.contains('\\') {
+                if let Token::Literal(_) | Token::Quoted(_) = token {
+                     let s = &input[range.clone()];
+                     if self.escapes_enabled() && s.contains('\\') {
                          // Switch to owned!
                          let mut res = String::with_capacity(input.len() + 16);
                          res.push_str(&input[..range.start]);
-                         Self::unescape_into(&mut res, s);
+                         self.unescape_into(&mut res, s, range.start)?;
                          result = Some(res);
                      }
                 }
@@ -285,8 +524,8 @@ impl<'a> Interpolator<'a> {
         if let Some(mut res) = result {
             if last_pos < input.len() {
                 let tail = &input[last_pos..];
-                 if self.config.features.escapes && tail.contains('\\') {
-                     Self::unescape_into(&mut res, tail);
+                 if self.escapes_enabled() && tail.contains('\\') {
+                     self.unescape_into(&mut res, tail, last_pos)?;
                  } else {
                      res.push_str(tail);
                  }
@@ -295,9 +534,9 @@ impl<'a> Interpolator<'a> {
         } else {
              // Input might have escapes that need processing even if no variables
              // BUT if preserve_cmd_escapes is true, we return as-is (escapes preserved)
-             if self.config.features.escapes && input.contains('\\') && !preserve_cmd_escapes {
+             if self.escapes_enabled() && input.contains('\\') && !preserve_cmd_escapes {
                  let mut res = String::with_capacity(input.len());
-                 Self::unescape_into(&mut res, input);
+                 self.unescape_into(&mut res, input, 0)?;
                  Ok(Cow::Owned(res))
              } else {
                  Ok(Cow::Borrowed(input))
@@ -305,100 +544,434 @@ impl<'a> Interpolator<'a> {
         }
     }
     
-    /// Unescape standard escape sequences in a string.
+    /// Expand a tilde prefix: `~` is `HOME`, `~+` is `PWD`, `~-` is `OLDPWD` and
+    /// `~user` comes from the user directory resolver, which is also the fallback
+    /// for `~` without `HOME`. Returns `None` if the directory is unknown.
+    fn expand_tilde(&self, user: &str, depth: usize, preserve_cmd_escapes: bool) -> Result<Option<String>, Error> {
+        let var = match user {
+            "" => Some("HOME"),
+            "+" => Some("PWD"),
+            "-" => Some("OLDPWD"),
+            _ => None,
+        };
+        let value = match var {
+            Some(var) => self.lookup(var)?,
+            None => None,
+        };
+        if let Some(value) = value {
+            return Ok(Some(self.resolve_value(&value, depth, preserve_cmd_escapes)?.into_owned()));
+        }
+        if user == "+" || user == "-" {
+            return Ok(None);
+        }
+
+        Ok(self.user_dirs.home_dir(user).map(|dir| {
+            if preserve_cmd_escapes {
+                // The directory is literal, so protect it from the command pass
+                let mut protected = String::with_capacity(dir.len());
+                push_protected(&mut protected, &dir);
+                protected
+            } else {
+                dir
+            }
+        }))
+    }
+
+    /// Unescape escape sequences in a string, in the configured dialect.
     /// Note: \` and \$ are handled by the scanner as Escape tokens, not here.
-    fn unescape_into(buf: &mut String, s: &str) {
-        let mut chars = s.chars().peekable();
-        while let Some(c) = chars.next() {
-            if c == '\\' {
-                match chars.next() {
-                    Some('n') => buf.push('\n'),
-                    Some('r') => buf.push('\r'),
-                    Some('t') => buf.push('\t'),
-                    Some('\\') => buf.push('\\'),
-                    Some('"') => buf.push('"'),
-                    Some('\'') => buf.push('\''),
-                    // Note: \` and \$ won't appear here - they're emitted as Escape tokens
-                    Some(other) => {
-                        // Unknown escape: Bash behavior is \c -> c
-                        buf.push(other);
+    fn unescape_into(&self, buf: &mut String, s: &str, pos: usize) -> Result<(), Error> {
+        escape::unescape_into(buf, s, self.config.escape_style, pos)
+    }
+
+    fn escapes_enabled(&self) -> bool {
+        self.config.features.escapes && self.config.escape_style != EscapeStyle::None
+    }
+    
+    /// Resolve a variable and apply its operator. `pos` is the position of the
+    /// expression in the input, used for error reporting.
+    fn resolve_variable<'b>(&self, name: &str, op: Option<&Operator<'b>>, pos: usize, depth: usize, preserve_cmd_escapes: bool) -> Result<Cow<'b, str>, Error> {
+        if let Some((items, skip)) = self.list(name)? {
+            return self.resolve_list(name, &items, skip, op, pos, depth, preserve_cmd_escapes);
+        }
+        // ${!PREFIX*} lists names, PREFIX itself is not read
+        if matches!(op, Some(Operator::Names { .. })) && self.config.features.indirection {
+            return self.apply_operator(name, None, op, pos, depth, preserve_cmd_escapes);
+        }
+        let val_opt = match scanner::split_subscript(name) {
+            Some((array, index)) => {
+                let index = self.resolve(index, depth + 1, false)?;
+                let index = self.evaluate_arithmetic(&index, pos, depth)?;
+                self.element(array, index)?
+            },
+            None => self.lookup(name)?,
+        };
+        self.apply_operator(name, val_opt, op, pos, depth, preserve_cmd_escapes)
+    }
+
+    /// Resolve `$@`, `$*`, `${ARR[@]}` or `${ARR[*]}`. `items[skip..]` are the
+    /// elements, while slicing with `${@:offset}` also covers `$0` in `items[0]`.
+    /// The elements are joined with a space, or the first character of `IFS` for `*`.
+    #[allow(clippy::too_many_arguments)]
+    fn resolve_list<'b>(&self, name: &str, items: &[Cow<'a, str>], skip: usize, op: Option<&Operator<'b>>, pos: usize, depth: usize, preserve_cmd_escapes: bool) -> Result<Cow<'b, str>, Error> {
+        let separator = if name == "*" || name.ends_with("[*]") { self.ifs_separator()? } else { Cow::Borrowed(" ") };
+        let features = &self.config.features;
+        // Without positional parameters there is no `$0` to skip either
+        let elements = items.get(skip..).unwrap_or_default();
+
+        match op {
+            Some(Operator::Length) if features.lengths => Ok(Cow::Owned(elements.len().to_string())),
+            Some(&Operator::Substring { offset, length }) if features.substrings => {
+                let offset = self.resolve_index(offset, pos, depth)?;
+                let length = length.map(|l| self.resolve_index(l, pos, depth)).transpose()?;
+                let start = if offset < 0 { items.len() as i64 + offset } else { offset }.max(0) as usize;
+                let selected = items.get(start..).unwrap_or_default();
+                let selected = match length {
+                    Some(l) if l < 0 => {
+                        return Err(Error::SyntaxError(format!("${{{}}}: substring expression < 0", name), pos));
+                    },
+                    Some(l) => &selected[..selected.len().min(l as usize)],
+                    None => selected,
+                };
+                self.resolve_value(&selected.join(&separator), depth, preserve_cmd_escapes)
+            },
+            Some(Operator::Assign { .. }) if features.assignments => {
+                Err(Error::SyntaxError(format!("${}: cannot assign in this way", name), pos))
+            },
+            // Pattern and case operators apply to each element
+            Some(Operator::RemovePrefix { .. } | Operator::RemoveSuffix { .. } | Operator::Replace { .. } | Operator::Case { .. }) => {
+                let values = elements
+                    .iter()
+                    .map(|v| self.apply_operator(name, Some(v.clone()), op, pos, depth, preserve_cmd_escapes))
+                    .collect::<Result<Vec<_>, _>>()?;
+                Ok(Cow::Owned(values.join(&separator)))
+            },
+            // An empty list is unset, but not an error, as in bash with `set -u`
+            None if elements.is_empty() => Ok(Cow::Borrowed("")),
+            _ => {
+                let joined = (!elements.is_empty()).then(|| Cow::Owned(elements.join(&separator)));
+                self.apply_operator(name, joined, op, pos, depth, preserve_cmd_escapes)
+            },
+        }
+    }
+
+    /// Apply an operator to the value of a variable, or `None` if it is unset.
+    fn apply_operator<'b>(&self, name: &str, val_opt: Option<Cow<'a, str>>, op: Option<&Operator<'b>>, pos: usize, depth: usize, preserve_cmd_escapes: bool) -> Result<Cow<'b, str>, Error> {
+        let features = &self.config.features;
+
+        match op {
+            // ${VAR:-word} is gated by `defaults`, ${VAR-word} by `alternates`
+            Some(&Operator::Default { word, strict }) if (strict && features.defaults) || (!strict && features.alternates) => {
+                match val_opt {
+                    Some(v) if !(strict && v.is_empty()) => self.resolve_value(&v, depth, preserve_cmd_escapes),
+                    _ => self.resolve(word, depth + 1, preserve_cmd_escapes),
+                }
+            },
+            Some(&Operator::Assign { .. }) if features.assignments && (scanner::is_positional(name) || name.contains('[')) => {
+                Err(Error::SyntaxError(format!("${}: cannot assign in this way", name), pos))
+            },
+            Some(&Operator::Assign { word, strict }) if features.assignments => {
+                match val_opt {
+                    Some(v) if !(strict && v.is_empty()) => self.resolve_value(&v, depth, preserve_cmd_escapes),
+                    _ => {
+                        let value = self.resolve(word, depth + 1, preserve_cmd_escapes)?;
+                        self.assigned.borrow_mut().insert(name.to_string(), value.to_string());
+                        Ok(value)
                     }
-                    None => {
-                        // Trailing backslash
-                        buf.push('\\');
+                }
+            },
+            Some(&Operator::Conditional { word, strict }) if features.conditionals => {
+                match val_opt {
+                    Some(v) if !(strict && v.is_empty()) => self.resolve(word, depth + 1, preserve_cmd_escapes),
+                    _ => Ok(Cow::Borrowed("")),
+                }
+            },
+            Some(&Operator::Error { message, strict }) if features.errors => {
+                // `strict_unsets` makes ${VAR?message} reject empty values like ${VAR:?message}
+                let reject_empty = strict || self.config.strict_unsets;
+                match val_opt {
+                    Some(v) if !(reject_empty && v.is_empty()) => self.resolve_value(&v, depth, preserve_cmd_escapes),
+                    _ => {
+                        let message = self.resolve(message, depth + 1, false)?;
+                        Err(Error::RequiredVar(name.to_string(), message.into_owned()))
                     }
                 }
-            } else {
-                buf.push(c);
-            }
+            },
+            Some(Operator::Length) if features.lengths => {
+                match val_opt {
+                    Some(v) => {
+                        // Count the fully resolved value, including escapes
+                        let resolved = self.resolve_stored(&v, depth, false)?;
+                        Ok(Cow::Owned(resolved.chars().count().to_string()))
+                    },
+                    None => Err(Error::MissingVar(name.to_string())),
+                }
+            },
+            Some(&Operator::Substring { offset, length }) if features.substrings => {
+                let Some(v) = val_opt else {
+                    return Err(Error::MissingVar(name.to_string()));
+                };
+                let offset = self.resolve_index(offset, pos, depth)?;
+                let length = length.map(|l| self.resolve_index(l, pos, depth)).transpose()?;
+                let resolved = self.resolve_stored(&v, depth, preserve_cmd_escapes)?;
+                match substring(&resolved, offset, length) {
+                    Some(sub) => Ok(Cow::Owned(sub)),
+                    None => Err(Error::SyntaxError(format!("substring expression < 0 in ${{{}}}", name), pos)),
+                }
+            },
+            Some(Operator::RemovePrefix { pattern, longest } | Operator::RemoveSuffix { pattern, longest }) if features.pattern_removal => {
+                let Some(v) = val_opt else {
+                    return Err(Error::MissingVar(name.to_string()));
+                };
+                let resolved = self.resolve_stored(&v, depth, preserve_cmd_escapes)?;
+                let pattern = self.resolve(pattern, depth + 1, preserve_cmd_escapes)?;
+                let glob = GlobPattern::new(&pattern, pos)?;
+                let trimmed = if matches!(op, Some(Operator::RemovePrefix { .. })) {
+                    glob.remove_prefix(&resolved, *longest)
+                } else {
+                    glob.remove_suffix(&resolved, *longest)
+                };
+                Ok(Cow::Owned(trimmed.to_string()))
+            },
+            Some(&Operator::Replace { pattern, replacement, mode }) if features.pattern_replacement => {
+                let Some(v) = val_opt else {
+                    return Err(Error::MissingVar(name.to_string()));
+                };
+                let resolved = self.resolve_stored(&v, depth, preserve_cmd_escapes)?;
+                let pattern = self.resolve(pattern, depth + 1, preserve_cmd_escapes)?;
+                let replacement = self.resolve(replacement, depth + 1, preserve_cmd_escapes)?;
+                let glob = GlobPattern::new(&pattern, pos)?;
+                Ok(Cow::Owned(glob.replace(&resolved, &replacement, mode)))
+            },
+            Some(&Operator::Case { mode, pattern }) if self.case_enabled(mode) => {
+                let Some(v) = val_opt else {
+                    return Err(Error::MissingVar(name.to_string()));
+                };
+                let resolved = self.resolve_stored(&v, depth, preserve_cmd_escapes)?;
+                // An empty pattern matches every character
+                let glob = if pattern.is_empty() {
+                    None
+                } else {
+                    let pattern = self.resolve(pattern, depth + 1, preserve_cmd_escapes)?;
+                    Some(GlobPattern::new(&pattern, pos)?)
+                };
+                Ok(Cow::Owned(convert_case(&resolved, mode, glob.as_ref())))
+            },
+            Some(&Operator::Names { star }) if features.indirection => {
+                let mut names: Vec<String> = self.context.keys().unwrap_or_default().into_iter()
+                    .map(Cow::into_owned)
+                    .chain(self.assigned.borrow().keys().cloned())
+                    .filter(|k| k.starts_with(name))
+                    .collect();
+                names.sort();
+                names.dedup();
+                let separator = if star { self.ifs_separator()? } else { Cow::Borrowed(" ") };
+                Ok(Cow::Owned(names.join(&separator)))
+            },
+            // No modifier, or the modifier's feature is disabled
+            _ => match val_opt {
+                Some(v) => self.resolve_value(&v, depth, preserve_cmd_escapes),
+                None => Err(Error::MissingVar(name.to_string())),
+            },
         }
     }
-    
-    fn resolve_variable<'b>(&self, name: &str, default: Option<&'b str>, strict: bool, conditional: bool, depth: usize, preserve_cmd_escapes: bool) -> Result<Cow<'b, str>, Error> {
-        let val_opt = self.context.get_value(name);
-
-        // Determine effective modifier based on feature flags
-        let (use_default, use_alternate, use_conditional) = match (default, strict, conditional) {
-            (Some(_), true, false) => (self.config.features.defaults, false, false),      // :-
-            (Some(_), false, false) => (false, self.config.features.alternates, false),   // -
-            (Some(_), _, true) => (false, false, self.config.features.conditionals),      // :+ or +
-            _ => (false, false, false), // No modifier or not relevant
-        };
 
-        let effective_default = if use_default || use_alternate || use_conditional {
-            default
+    fn case_enabled(&self, mode: CaseMode) -> bool {
+        let features = &self.config.features;
+        match mode {
+            CaseMode::UpperFirst => features.upper_first,
+            CaseMode::UpperAll => features.upper_all,
+            CaseMode::LowerFirst => features.lower_first,
+            CaseMode::LowerAll => features.lower_all,
+        }
+    }
+
+    /// Evaluate an (already interpolated) arithmetic expression. Variables referenced
+    /// by bare name are themselves evaluated as expressions, and unset ones are 0.
+    fn evaluate_arithmetic(&self, expr: &str, pos: usize, depth: usize) -> Result<i64, Error> {
+        if depth > self.config.max_depth {
+            return Err(Error::RecursiveLookup(expr.to_string()));
+        }
+
+        arithmetic::evaluate(expr, pos, &|name| match self.lookup(name)? {
+            Some(v) => {
+                let value = self.resolve_stored(&v, depth, false)?;
+                self.evaluate_arithmetic(value.trim(), pos, depth + 1)
+            },
+            None => Ok(0),
+        })
+    }
+
+    /// Resolve a substring offset or length, an arithmetic expression like
+    /// `${VAR:$N-1}`, to an integer.
+    fn resolve_index(&self, expr: &str, pos: usize, depth: usize) -> Result<i64, Error> {
+        let resolved = self.resolve(expr, depth + 1, false)?;
+        self.evaluate_arithmetic(&resolved, pos, depth)
+    }
+
+    /// Resolve the name of a variable to look up. Dynamic names like
+    /// `${CONFIG_${ENV}}` are interpolated first, then indirect references
+    /// `${!name}` are followed.
+    fn resolve_name<'n>(&self, name: &'n str, indirect: bool, depth: usize) -> Result<Cow<'n, str>, Error> {
+        let name = if name.contains('$') {
+            Cow::Owned(self.resolve(name, depth + 1, false)?.into_owned())
         } else {
-            None
+            Cow::Borrowed(name)
         };
 
-        if conditional {
-             if self.config.features.conditionals {
-                  match val_opt {
-                      Some(v) => {
-                           if strict && v.is_empty() {
-                               return Ok(Cow::Borrowed(""));
-                           }
-
-                           if let Some(def_raw) = effective_default {
-                               return self.resolve(def_raw, depth + 1, preserve_cmd_escapes);
-                           }
-                           return Ok(Cow::Borrowed(""));
-                      },
-                      None => return Ok(Cow::Borrowed("")),
-                  }
-             }
+        if !(indirect && self.config.features.indirection) {
+            return Ok(name);
+        }
+        match self.lookup(&name)? {
+            Some(v) => Ok(Cow::Owned(self.resolve_stored(&v, depth, false)?.into_owned())),
+            None => Err(Error::MissingVar(name.into_owned())),
         }
+    }
 
-        match (val_opt, strict) {
-             (Some(v), _) => {
-                 if !conditional && strict && v.is_empty() {
-                      if self.config.features.defaults {
-                           if let Some(def_raw) = default {
-                               return self.resolve(def_raw, depth + 1, preserve_cmd_escapes);
-                           }
-                      }
-                 }
+    /// The separator used when joining with `*`: the first character of `IFS`,
+    /// a space if `IFS` is unset, or nothing if it is empty.
+    fn ifs_separator(&self) -> Result<Cow<'static, str>, Error> {
+        Ok(match self.lookup("IFS")? {
+            Some(ifs) => Cow::Owned(ifs.chars().next().map(String::from).unwrap_or_default()),
+            None => Cow::Borrowed(" "),
+        })
+    }
 
-                 let resolved = self.resolve(v, depth + 1, preserve_cmd_escapes)?;
-                 Ok(Cow::Owned(resolved.into_owned()))
-             },
-             (None, _) => {
-                 if !conditional {
-                     if strict && self.config.features.defaults {
-                          if let Some(def_raw) = default {
-                              return self.resolve(def_raw, depth + 1, preserve_cmd_escapes);
-                          }
-                     }
+    /// Look up a variable, preferring values assigned earlier in this interpolation.
+    /// Fails if the provider cannot tell whether the variable is set.
+    fn lookup(&self, name: &str) -> Result<Option<Cow<'a, str>>, Error> {
+        if scanner::is_positional(name) {
+            return self.positional(name);
+        }
+        if scanner::is_special(name) {
+            return Ok(self.context.special_value(name).map(Cow::Owned));
+        }
+        if let Some(v) = self.assigned.borrow().get(name) {
+            return Ok(Some(Cow::Owned(v.clone())));
+        }
+        let value = self.context.try_get_value(name).map_err(|source| Error::ProviderError {
+            name: name.to_string(),
+            source: source.into(),
+        })?;
+        Ok(match value {
+            Some(v) => Some(v),
+            // $ARR is the first element of an array
+            None => self.context.get_array(name).and_then(|elements| elements.into_iter().next()),
+        })
+    }
 
-                     if !strict && self.config.features.alternates {
-                          if let Some(def_raw) = default {
-                              return self.resolve(def_raw, depth + 1, preserve_cmd_escapes);
-                          }
-                     }
-                 }
+    /// Look up a positional parameter. `$@` and `$*` join all of them, with a
+    /// space and the first character of `IFS` respectively, and are unset if
+    /// there are none.
+    fn positional(&self, name: &str) -> Result<Option<Cow<'a, str>>, Error> {
+        let params = self.params();
+        Ok(match name {
+            "#" => Some(Cow::Owned(params.len().to_string())),
+            "@" | "*" if params.is_empty() => None,
+            "@" => Some(Cow::Owned(params.join(" "))),
+            "*" => Some(Cow::Owned(params.join(&self.ifs_separator()?))),
+            _ => name.parse::<usize>().ok().and_then(|index| self.args.unwrap_or_default().get(index)).map(|arg| Cow::Borrowed(*arg)),
+        })
+    }
 
-                 Err(Error::MissingVar(name.to_string()))
-             }
+    /// The items of `$@`, `$*`, `${ARR[@]}` or `${ARR[*]}`, and how many of them
+    /// come before the elements: `$0` for positional parameters. `None` for
+    /// other names. A scalar variable is an array of one element.
+    fn list(&self, name: &str) -> Result<Option<List<'a>>, Error> {
+        if matches!(name, "@" | "*") {
+            return Ok(Some((self.args.unwrap_or_default().iter().map(|arg| Cow::Borrowed(*arg)).collect(), 1)));
+        }
+        let Some(array) = name.strip_suffix("[@]").or_else(|| name.strip_suffix("[*]")) else {
+            return Ok(None);
+        };
+        let items = match self.array(array) {
+            Some(elements) => elements,
+            None => self.lookup(array)?.into_iter().collect(),
+        };
+        Ok(Some((items, 0)))
+    }
+
+    /// Element `index` of an array, counting from the end if negative.
+    fn element(&self, array: &str, index: i64) -> Result<Option<Cow<'a, str>>, Error> {
+        let Some(elements) = self.array(array) else {
+            return if index == 0 || index == -1 { self.lookup(array) } else { Ok(None) };
+        };
+        let index = if index < 0 { elements.len() as i64 + index } else { index };
+        Ok(usize::try_from(index).ok().and_then(|index| elements.into_iter().nth(index)))
+    }
+
+    /// Elements of an array variable. Values assigned during interpolation are
+    /// scalars, which hide an array of the same name.
+    fn array(&self, name: &str) -> Option<Vec<Cow<'a, str>>> {
+        if self.assigned.borrow().contains_key(name) {
+            return None;
+        }
+        self.context.get_array(name)
+    }
+
+    /// The positional parameters after `$0`.
+    fn params(&self) -> &'a [&'a str] {
+        self.args.unwrap_or_default().get(1..).unwrap_or_default()
+    }
+
+    /// Recursively resolve a variable's value. The result is always owned since
+    /// it does not borrow from the input being interpolated.
+    fn resolve_value<'b>(&self, value: &str, depth: usize, preserve_cmd_escapes: bool) -> Result<Cow<'b, str>, Error> {
+        let resolved = self.resolve_stored(value, depth, preserve_cmd_escapes)?;
+        Ok(Cow::Owned(resolved.into_owned()))
+    }
+}
+
+/// Escapes the async command pass relies on, kept as-is by the first pass.
+fn is_cmd_escape(c: char) -> bool {
+    c == '$' || c == '`' || c == '\''
+}
+
+/// Append literal text, escaping `$`, `` ` `` and `'` so the command pass leaves it alone.
+fn push_protected(buf: &mut String, text: &str) {
+    for c in text.chars() {
+        if is_cmd_escape(c) {
+            buf.push('\\');
+        }
+        buf.push(c);
+    }
+}
+
+/// Bash substring semantics on characters. A negative `offset` counts from the end,
+/// and a negative `length` gives the end position counted from the end.
+/// Returns `None` if the end position falls before the start.
+fn substring(value: &str, offset: i64, length: Option<i64>) -> Option<String> {
+    let len = value.chars().count() as i64;
+    let start = if offset < 0 { len + offset } else { offset };
+    if start < 0 || start > len {
+        return Some(String::new());
+    }
+    let end = match length {
+        Some(l) if l < 0 => len + l,
+        Some(l) => (start + l).min(len),
+        None => len,
+    };
+    if end < start {
+        return None;
+    }
+    Some(value.chars().skip(start as usize).take((end - start) as usize).collect())
+}
+
+/// Convert the case of `value`, only touching characters matched by `glob` (if any).
+fn convert_case(value: &str, mode: CaseMode, glob: Option<&GlobPattern>) -> String {
+    let upper = matches!(mode, CaseMode::UpperFirst | CaseMode::UpperAll);
+    let first_only = matches!(mode, CaseMode::UpperFirst | CaseMode::LowerFirst);
+
+    let mut result = String::with_capacity(value.len());
+    let mut buf = [0u8; 4];
+    for (i, c) in value.chars().enumerate() {
+        let selected = (!first_only || i == 0) && glob.is_none_or(|g| g.matches(c.encode_utf8(&mut buf)));
+        if !selected {
+            result.push(c);
+        } else if upper {
+            result.extend(c.to_uppercase());
+        } else {
+            result.extend(c.to_lowercase());
         }
     }
+    result
 }
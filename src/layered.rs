@@ -0,0 +1,129 @@
+use std::borrow::Cow;
+use std::fmt;
+use std::sync::Arc;
+
+use crate::context::VariableProvider;
+use crate::error::Error;
+
+/// A stack of named providers, such as command line overrides over `.env.local`
+/// over `.env` over the process environment over defaults.
+///
+/// Layers added first take precedence. `source` tells which layer supplied a
+/// variable, e.g. to explain where a setting came from.
+#[derive(Clone, Default)]
+pub struct LayeredProvider {
+    layers: Vec<(String, Arc<dyn VariableProvider + Send + Sync>)>,
+}
+
+impl fmt::Debug for LayeredProvider {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("LayeredProvider")
+            .field("layers", &self.layer_names().collect::<Vec<_>>())
+            .finish()
+    }
+}
+
+impl LayeredProvider {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a layer below the existing ones.
+    pub fn with_layer(mut self, name: impl Into<String>, provider: impl VariableProvider + Send + Sync + 'static) -> Self {
+        self.push_layer(name, provider);
+        self
+    }
+
+    /// Add a layer below the existing ones.
+    pub fn push_layer(&mut self, name: impl Into<String>, provider: impl VariableProvider + Send + Sync + 'static) {
+        self.layers.push((name.into(), Arc::new(provider)));
+    }
+
+    /// Names of the layers, highest precedence first.
+    pub fn layer_names(&self) -> impl Iterator<Item = &str> {
+        self.layers.iter().map(|(name, _)| name.as_str())
+    }
+
+    /// Name of the layer that supplies `key`, as a scalar or an array, if any.
+    /// `None` if a layer fails to look it up, see [`LayeredProvider::try_source`].
+    pub fn source(&self, key: &str) -> Option<&str> {
+        self.try_source(key).ok().flatten()
+    }
+
+    /// Value of `key` together with the name of the layer that supplied it.
+    /// `None` if a layer fails to look it up, see [`LayeredProvider::try_get_with_source`].
+    pub fn get_with_source(&self, key: &str) -> Option<(&str, Cow<'_, str>)> {
+        self.try_get_with_source(key).ok().flatten()
+    }
+
+    /// Like [`LayeredProvider::source`], but fails with `Error::ProviderError`
+    /// if a layer fails to look up `key`, as interpolating it would.
+    pub fn try_source(&self, key: &str) -> Result<Option<&str>, Error> {
+        let found = self.find(key).map_err(|source| provider_error(key, source))?;
+        Ok(found.map(|(name, _)| name))
+    }
+
+    /// Like [`LayeredProvider::get_with_source`], but fails with `Error::ProviderError`
+    /// if a layer fails to look up `key`, as interpolating it would.
+    pub fn try_get_with_source(&self, key: &str) -> Result<Option<(&str, Cow<'_, str>)>, Error> {
+        let found = self.find(key).map_err(|source| provider_error(key, source))?;
+        Ok(found.and_then(|(name, value)| Some((name, value?))))
+    }
+
+    /// The first layer with `key`, and its value unless it is an array there.
+    /// An array in a higher layer hides a scalar in a lower one.
+    #[allow(clippy::type_complexity)]
+    fn find(&self, key: &str) -> Result<Option<(&str, Option<Cow<'_, str>>)>, Box<dyn std::error::Error + Send + Sync>> {
+        for (name, provider) in &self.layers {
+            if let Some(value) = provider.try_get_value(key)? {
+                return Ok(Some((name, Some(value))));
+            }
+            if provider.get_array(key).is_some() {
+                return Ok(Some((name, None)));
+            }
+        }
+        Ok(None)
+    }
+}
+
+fn provider_error(key: &str, source: Box<dyn std::error::Error + Send + Sync>) -> Error {
+    Error::ProviderError { name: key.to_string(), source: source.into() }
+}
+
+impl VariableProvider for LayeredProvider {
+    fn get_value(&self, key: &str) -> Option<Cow<'_, str>> {
+        self.get_with_source(key).map(|(_, value)| value)
+    }
+
+    fn try_get_value(&self, key: &str) -> Result<Option<Cow<'_, str>>, Box<dyn std::error::Error + Send + Sync>> {
+        Ok(self.find(key)?.and_then(|(_, value)| value))
+    }
+
+    fn keys(&self) -> Option<Vec<Cow<'_, str>>> {
+        let mut keys: Vec<_> = self
+            .layers
+            .iter()
+            .flat_map(|(_, provider)| provider.keys().unwrap_or_default())
+            .collect();
+        keys.sort();
+        keys.dedup();
+        Some(keys)
+    }
+
+    fn get_array(&self, key: &str) -> Option<Vec<Cow<'_, str>>> {
+        // A scalar in a higher layer hides an array in a lower one
+        for (_, provider) in &self.layers {
+            if let Some(elements) = provider.get_array(key) {
+                return Some(elements);
+            }
+            if provider.get_value(key).is_some() {
+                return None;
+            }
+        }
+        None
+    }
+
+    fn special_value(&self, name: &str) -> Option<String> {
+        self.layers.iter().find_map(|(_, provider)| provider.special_value(name))
+    }
+}
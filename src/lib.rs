@@ -1,25 +1,51 @@
+mod argv;
+mod arithmetic;
+mod brace;
 mod config;
 mod context;
+mod env;
 mod error;
+mod escape;
+mod glob;
 mod interpolator;
+mod layered;
 pub mod scanner;
 
 use std::borrow::Cow;
 use std::collections::HashMap;
+use std::fmt;
+use std::sync::Arc;
 
 use crate::interpolator::Interpolator;
 
-pub use config::{Config, FeatureConfig};
-pub use context::{SimpleContext, VariableProvider};
+pub use config::{Config, EscapeStyle, FeatureConfig};
+pub use context::{SimpleContext, SystemUserDirs, UserDirResolver, VariableProvider};
+pub use env::EnvProvider;
+pub use layered::LayeredProvider;
 pub use error::Error;
 
 use std::collections::HashSet;
 
 /// Main entry point for the Germi interpolation engine.
-#[derive(Debug, Clone)]
-pub struct Germi {
+///
+/// Variables come from a `SimpleContext` by default. Use `Germi::with_provider`
+/// to look them up in any other `VariableProvider`.
+#[derive(Clone)]
+pub struct Germi<P = SimpleContext> {
     config: Config,
-    context: SimpleContext,
+    context: P,
+    /// Home directory lookup for tilde expansion, `SystemUserDirs` if unset
+    user_dirs: Option<Arc<dyn UserDirResolver + Send + Sync>>,
+}
+
+impl<P: fmt::Debug> fmt::Debug for Germi<P> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Germi")
+            .field("config", &self.config)
+            .field("context", &self.context)
+            .field("user_dirs", &self.user_dirs.as_ref().map(|_| ".."))
+            .finish()
+    }
 }
 
 impl Default for Germi {
@@ -30,17 +56,11 @@ impl Default for Germi {
 
 impl Germi {
     pub fn new() -> Self {
-        Self {
-            config: Config::default(),
-            context: SimpleContext::new(),
-        }
+        Self::with_config(Config::default())
     }
 
     pub fn with_config(config: Config) -> Self {
-        Self {
-            config,
-            context: SimpleContext::new(),
-        }
+        Self::with_provider_and_config(SimpleContext::new(), config)
     }
 
     /// Add a variable to the internal context.
@@ -48,19 +68,127 @@ impl Germi {
         self.context.insert(key, value);
     }
 
+    /// Add an array variable to the internal context, for `${ARR[i]}` and `${ARR[@]}`.
+    pub fn add_array<I>(&mut self, key: impl Into<String>, values: I)
+    where
+        I: IntoIterator,
+        I::Item: Into<String>,
+    {
+        self.context.insert_array(key, values);
+    }
+
+    /// Interpolate a string, keeping variables assigned by `${VAR:=value}` in the
+    /// context so that subsequent calls see them.
+    pub fn interpolate_mut<'b>(&mut self, input: &'b str) -> Result<Cow<'b, str>, Error> {
+        let (result, assignments) = self.interpolate_with_assignments(input)?;
+        for (key, value) in assignments {
+            self.context.insert(key, value);
+        }
+        Ok(result)
+    }
+}
+
+impl Germi<EnvProvider> {
+    /// Create an engine that reads variables from the process environment on
+    /// every lookup. Use `Germi::with_provider(EnvProvider::snapshot())` for a
+    /// copy taken once, or to filter by prefix.
+    pub fn from_env() -> Self {
+        Self::with_provider(EnvProvider::live())
+    }
+}
+
+impl<P: VariableProvider> Germi<P> {
+    /// Create an engine that looks variables up in `provider`.
+    pub fn with_provider(provider: P) -> Self {
+        Self::with_provider_and_config(provider, Config::default())
+    }
+
+    pub fn with_provider_and_config(provider: P, config: Config) -> Self {
+        Self {
+            config,
+            context: provider,
+            user_dirs: None,
+        }
+    }
+
+    pub fn provider(&self) -> &P {
+        &self.context
+    }
+
+    pub fn provider_mut(&mut self) -> &mut P {
+        &mut self.context
+    }
+
+    /// Set the resolver for `~user`, also used for `~` when there is no `HOME`
+    /// variable. Defaults to `SystemUserDirs`.
+    pub fn set_user_dirs(&mut self, user_dirs: impl UserDirResolver + Send + Sync + 'static) {
+        self.user_dirs = Some(Arc::new(user_dirs));
+    }
+
+    fn interpolator(&self) -> Interpolator<'_> {
+        let interpolator = Interpolator::new(&self.context, &self.config);
+        match &self.user_dirs {
+            Some(user_dirs) => interpolator.with_user_dirs(user_dirs.as_ref()),
+            None => interpolator,
+        }
+    }
+
     /// Interpolate a string using variables from the context.
     pub fn interpolate<'b>(&self, input: &'b str) -> Result<Cow<'b, str>, Error> {
-        let interpolator = Interpolator::new(&self.context, &self.config);
+        let interpolator = self.interpolator();
         interpolator.interpolate(input)
     }
 
+    /// Interpolate a string, also returning the variables assigned by
+    /// `${VAR:=value}`, so that they can be stored in the provider.
+    pub fn interpolate_with_assignments<'b>(&self, input: &'b str) -> Result<(Cow<'b, str>, HashMap<String, String>), Error> {
+        let interpolator = self.interpolator();
+        let result = interpolator.interpolate(input)?;
+        Ok((result, interpolator.into_assignments()))
+    }
+
+    /// Expand braces like bash, e.g. `web-{a,b}{01..03}`, and interpolate each
+    /// resulting word. The input is treated as a single word. Expansions giving
+    /// more than 100000 words are a `SyntaxError`.
+    pub fn expand_words(&self, input: &str) -> Result<Vec<String>, Error> {
+        let interpolator = self.interpolator();
+        brace::expand(input)?
+            .iter()
+            .map(|word| interpolator.interpolate(word).map(Cow::into_owned))
+            .collect()
+    }
+
+    /// Interpolate a command line and split it into arguments, honouring quotes
+    /// and `IFS` like the shell. Useful for building `std::process::Command`
+    /// arguments without going through `sh -c`.
+    pub fn interpolate_argv(&self, input: &str) -> Result<Vec<String>, Error> {
+        self.interpolator().interpolate_argv(input)
+    }
+
+    /// Interpolate a string with positional parameters `$0`, `$1`, ..., `$@`, `$*`
+    /// and `$#`. As with `std::env::args()`, `args[0]` is `$0`.
+    ///
+    /// Without positional parameters, `interpolate` leaves `$1` and friends as-is,
+    /// while braced forms like `${1}` are unset variables.
+    pub fn interpolate_with_args<'b>(&self, input: &'b str, args: &[impl AsRef<str>]) -> Result<Cow<'b, str>, Error> {
+        let args: Vec<&str> = args.iter().map(AsRef::as_ref).collect();
+        self.interpolator().with_args(&args).interpolate(input)
+    }
+
+    /// Like `interpolate_argv`, with positional parameters. `"$@"` gives one
+    /// argument per parameter, and none if there are no parameters.
+    pub fn interpolate_argv_with_args(&self, input: &str, args: &[impl AsRef<str>]) -> Result<Vec<String>, Error> {
+        let args: Vec<&str> = args.iter().map(AsRef::as_ref).collect();
+        self.interpolator().with_args(&args).interpolate_argv(input)
+    }
+
     /// Interpolate a string using temporary additional variables.
     pub fn interpolate_with<'b>(
         &self,
         input: &'b str,
         extra_vars: &HashMap<String, String>,
     ) -> Result<Cow<'b, str>, Error> {
-        let interpolator = Interpolator::new(&self.context, &self.config);
+        let interpolator = self.interpolator();
         interpolator.interpolate_with(input, extra_vars)
     }
 
@@ -68,23 +196,58 @@ impl Germi {
     /// Requires `async` feature.
     #[cfg(feature = "async")]
     pub async fn interpolate_async<'b>(&self, input: &'b str) -> Result<Cow<'b, str>, Error> {
-        let interpolator = Interpolator::new(&self.context, &self.config);
+        let interpolator = self.interpolator();
         interpolator.interpolate_async(input).await
     }
 }
 
 pub fn find_variable_references(input: &str) -> Vec<String> {
-    let mut scanner = scanner::Scanner::new(input);
     let mut variables = HashSet::new();
-
-    while let Ok(Some((token, _))) = scanner.scan_next() {
-        if let scanner::Token::Variable { name, .. } = token {
-            variables.insert(name.to_string());
-        }
-    }
+    collect_variable_references(input, &mut variables);
 
     // Convert to sorted Vec for deterministic ordering
     let mut result: Vec<String> = variables.into_iter().collect();
     result.sort();
     result
 }
+
+fn collect_variable_references(input: &str, variables: &mut HashSet<String>) {
+    let mut scanner = scanner::Scanner::new(input);
+
+    while let Ok(Some((token, _))) = scanner.scan_next() {
+        match token {
+            // Both $VAR and bare names like PORT in $((PORT + 1)) are variables
+            scanner::Token::Arithmetic(expr) => {
+                collect_variable_references(expr, variables);
+                let mut scanner = scanner::Scanner::new(expr);
+                while let Ok(Some((token, _))) = scanner.scan_next() {
+                    if let scanner::Token::Literal(text) = token {
+                        variables.extend(arithmetic::identifiers(text).into_iter().map(String::from));
+                    }
+                }
+            }
+            // ${!PREFIX*} names a prefix, not a variable
+            scanner::Token::Variable { op: Some(scanner::Operator::Names { .. }), .. } => {}
+            // Positional and special parameters are not variables
+            scanner::Token::Variable { name, .. } if scanner::is_positional(name) || scanner::is_special(name) => {}
+            scanner::Token::Variable { name, .. } => {
+                // ${ARR[0]} refers to ARR, and ${ARR[$I]} also to I
+                let name = match scanner::split_subscript(name) {
+                    Some((array, index)) => {
+                        collect_variable_references(index, variables);
+                        array
+                    },
+                    None => name,
+                };
+                // A dynamic name like ${CONFIG_${ENV}} is not known until it is
+                // interpolated, but the variables it is built from are
+                if name.contains('$') {
+                    collect_variable_references(name, variables);
+                } else {
+                    variables.insert(name.to_string());
+                }
+            }
+            _ => {}
+        }
+    }
+}
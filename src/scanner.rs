@@ -4,36 +4,132 @@ use crate::error::Error;
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum Token<'a> {
     Literal(&'a str),
+    /// Single-quoted text using '...' syntax, holding the body without the quotes.
+    /// Variables are not expanded inside.
+    Quoted(&'a str),
     Variable {
         name: &'a str,
-        default: Option<&'a str>,
-        /// true means `${VAR:-val}`, false means `${VAR-val}` (or similar logic for +)
-        strict: bool,
-        /// If true, this is a conditional substitution like `${VAR:+val}`
-        conditional: bool,
+        /// If true, this is an indirect reference like `${!VAR}`: the value of
+        /// `name` is the name of the variable to expand
+        indirect: bool,
+        /// Parameter expansion operator following the name, if any
+        op: Option<Operator<'a>>,
     },
     /// Command substitution using $(cmd) syntax
     Command(&'a str),
+    /// Arithmetic expansion using $((expr)) syntax
+    Arithmetic(&'a str),
+    /// ANSI-C quoted string using $'...' syntax, holding the undecoded body
+    AnsiCQuoted(&'a str),
     /// Command substitution using legacy `cmd` backtick syntax
     BacktickCommand(&'a str),
     /// Escaped character that should be output literally (e.g., \` -> `, \$ -> $)
     /// This preserves escape semantics through both sync and async passes
     Escape(char),
+    /// Tilde prefix (`~`, `~+`, `~-` or `~user`) at the start of a word, after
+    /// `:` or after `NAME=`, holding the text after the tilde
+    Tilde(&'a str),
+    /// A `"` opening or closing a double-quoted region. Only emitted by a
+    /// scanner created with [`Scanner::with_quotes`].
+    DoubleQuote,
+}
+
+/// Parameter expansion operator inside a braced variable.
+///
+/// `strict` is true for the colon forms (`${VAR:-val}`), which treat an empty
+/// value the same as an unset one.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Operator<'a> {
+    /// `${VAR:-word}` or `${VAR-word}`
+    Default { word: &'a str, strict: bool },
+    /// `${VAR:+word}` or `${VAR+word}`
+    Conditional { word: &'a str, strict: bool },
+    /// `${VAR:?message}` or `${VAR?message}`
+    Error { message: &'a str, strict: bool },
+    /// `${VAR:=word}` or `${VAR=word}`
+    Assign { word: &'a str, strict: bool },
+    /// `${#VAR}`, the length of the value in characters
+    Length,
+    /// `${VAR:offset}` or `${VAR:offset:length}`, counted in characters
+    Substring { offset: &'a str, length: Option<&'a str> },
+    /// `${VAR#pattern}` or, if `longest`, `${VAR##pattern}`
+    RemovePrefix { pattern: &'a str, longest: bool },
+    /// `${VAR%pattern}` or, if `longest`, `${VAR%%pattern}`
+    RemoveSuffix { pattern: &'a str, longest: bool },
+    /// `${VAR/pattern/replacement}` and its `//`, `/#` and `/%` variants
+    Replace { pattern: &'a str, replacement: &'a str, mode: ReplaceMode },
+    /// `${VAR^}`, `${VAR^^}`, `${VAR,}` or `${VAR,,}`, optionally followed by a
+    /// pattern restricting which characters are converted
+    Case { mode: CaseMode, pattern: &'a str },
+    /// `${!PREFIX*}` or `${!PREFIX@}`, the names of variables starting with the
+    /// prefix. `star` is true for `*`, which joins with the first character of `IFS`.
+    Names { star: bool },
+}
+
+/// Case conversion applied by `${VAR^}` and friends.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CaseMode {
+    /// `${VAR^}`, uppercase the first character
+    UpperFirst,
+    /// `${VAR^^}`, uppercase every character
+    UpperAll,
+    /// `${VAR,}`, lowercase the first character
+    LowerFirst,
+    /// `${VAR,,}`, lowercase every character
+    LowerAll,
+}
+
+/// Which matches of the pattern `${VAR/pattern/replacement}` replaces.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReplaceMode {
+    /// `${VAR/pattern/rep}`, the first match
+    First,
+    /// `${VAR//pattern/rep}`, every match
+    All,
+    /// `${VAR/#pattern/rep}`, a match at the start
+    Prefix,
+    /// `${VAR/%pattern/rep}`, a match at the end
+    Suffix,
 }
 
 #[derive(Debug)]
 pub struct Scanner<'a> {
     source: &'a str,
     byte_idx: usize,
+    /// Shell-style quote processing, see [`Scanner::with_quotes`]
+    quotes: bool,
+    /// Position of the opening `"` while inside a double-quoted region
+    double_quote_start: Option<usize>,
+    /// Emit `\'` as an escape, see [`Scanner::with_quote_escapes`]
+    quote_escapes: bool,
 }
 
 impl<'a> Scanner<'a> {
     pub fn new(source: &'a str) -> Self {
-        Self { source, byte_idx: 0 }
+        Self { source, byte_idx: 0, quotes: false, double_quote_start: None, quote_escapes: false }
+    }
+
+    /// Create a scanner with shell-style quote processing.
+    ///
+    /// `"` delimiters are emitted as [`Token::DoubleQuote`]. Inside double quotes,
+    /// single quotes are ordinary characters and only `\$ \` \" \\` are escapes.
+    /// An unterminated `"` or `'` is reported as [`Error::UnclosedQuote`].
+    pub fn with_quotes(source: &'a str) -> Self {
+        Self { quotes: true, ..Self::new(source) }
+    }
+
+    /// Create a scanner for the output of the first async pass, in which `\'` is
+    /// a single quote protected from the command pass and emitted as [`Token::Escape`].
+    #[cfg(feature = "async")]
+    pub(crate) fn with_quote_escapes(source: &'a str) -> Self {
+        Self { quote_escapes: true, ..Self::new(source) }
     }
 
     pub fn scan_next(&mut self) -> Result<Option<(Token<'a>, std::ops::Range<usize>)>, Error> {
         if self.byte_idx >= self.source.len() {
+            if let Some(open) = self.double_quote_start {
+                return Err(Error::UnclosedQuote(open));
+            }
             return Ok(None);
         }
 
@@ -45,7 +141,11 @@ impl<'a> Scanner<'a> {
 
             // memchr only supports up to 3 chars, so we find min of two searches
             let pos_special = memchr::memchr3(b'$', b'\\', b'\'', rem);
-            let pos_backtick = memchr::memchr(b'`', rem);
+            let pos_backtick = if self.quotes {
+                memchr::memchr3(b'`', b'"', b'~', rem)
+            } else {
+                memchr::memchr2(b'`', b'~', rem)
+            };
             let combined = match (pos_special, pos_backtick) {
                 (Some(a), Some(b)) => Some(a.min(b)),
                 (Some(a), None) => Some(a),
@@ -62,7 +162,7 @@ impl<'a> Scanner<'a> {
                         // Check if next character is ` or $ (command-related escapes)
                         if abs_p + 1 < self.source.len() {
                             let next_byte = self.source.as_bytes()[abs_p + 1];
-                            if next_byte == b'`' || next_byte == b'$' {
+                            if next_byte == b'`' || next_byte == b'$' || (self.quote_escapes && next_byte == b'\'') {
                                 // Emit accumulated literal first
                                 if abs_p > start {
                                     let text = &self.source[start..abs_p];
@@ -74,6 +174,21 @@ impl<'a> Scanner<'a> {
                                 self.byte_idx = abs_p + 2; // skip both \ and the escaped char
                                 return Ok(Some((Token::Escape(escaped_char), abs_p..abs_p + 2)));
                             }
+                            if self.double_quote_start.is_some() {
+                                // Inside double quotes only \" and \\ are escapes,
+                                // other backslashes are literal
+                                if next_byte == b'"' || next_byte == b'\\' {
+                                    if abs_p > start {
+                                        let text = &self.source[start..abs_p];
+                                        self.byte_idx = abs_p;
+                                        return Ok(Some((Token::Literal(text), start..abs_p)));
+                                    }
+                                    self.byte_idx = abs_p + 2;
+                                    return Ok(Some((Token::Escape(next_byte as char), abs_p..abs_p + 2)));
+                                }
+                                current = abs_p + 1;
+                                continue;
+                            }
                             // Other escapes: skip and include in literal
                             let next_str = &self.source[abs_p + 1..];
                             if let Some(c) = next_str.chars().next() {
@@ -84,12 +199,20 @@ impl<'a> Scanner<'a> {
                         } else {
                             current = self.source.len();
                         }
+                    } else if char_found == b'\'' && self.double_quote_start.is_some() {
+                        // Single quotes are ordinary characters inside double quotes
+                        current = abs_p + 1;
                     } else if char_found == b'\'' {
-                        // Single quote block. Skip until closing quote.
+                        // Single quote block. Emit accumulated literal first
+                        if abs_p > start {
+                            let text = &self.source[start..abs_p];
+                            self.byte_idx = abs_p;
+                            return Ok(Some((Token::Literal(text), start..abs_p)));
+                        }
+
+                        // Skip until closing quote
                         let inner_start = abs_p + 1;
                         let inner_rem = &self.source.as_bytes()[inner_start..];
-                        
-                        let mut found_close = false;
                         let mut scan_pos = 0;
                         
                         // Scan for ' or \ inside
@@ -111,15 +234,17 @@ impl<'a> Scanner<'a> {
                                 }
                             } else {
                                 // Found closing '
-                                current = abs_q + 1;
-                                found_close = true;
-                                break;
+                                self.byte_idx = abs_q + 1;
+                                let body = &self.source[inner_start..abs_q];
+                                return Ok(Some((Token::Quoted(body), abs_p..abs_q + 1)));
                             }
                         }
-                        
-                        if !found_close {
-                            current = self.source.len();
+
+                        // Unterminated: the rest is literal
+                        if self.quotes {
+                            return Err(Error::UnclosedQuote(abs_p));
                         }
+                        current = self.source.len();
                     } else if char_found == b'$' {
                         // Found Variable start
 
@@ -140,6 +265,34 @@ impl<'a> Scanner<'a> {
                         } else {
                              unreachable!("parse_variable returned None");
                         }
+                    } else if char_found == b'~' {
+                        match self.tilde_prefix(abs_p) {
+                            Some(user) => {
+                                if abs_p > start {
+                                    let text = &self.source[start..abs_p];
+                                    self.byte_idx = abs_p;
+                                    return Ok(Some((Token::Literal(text), start..abs_p)));
+                                }
+                                let end = abs_p + 1 + user.len();
+                                self.byte_idx = end;
+                                return Ok(Some((Token::Tilde(user), abs_p..end)));
+                            },
+                            None => current = abs_p + 1,
+                        }
+                    } else if char_found == b'"' {
+                        if abs_p > start {
+                            let text = &self.source[start..abs_p];
+                            self.byte_idx = abs_p;
+                            return Ok(Some((Token::Literal(text), start..abs_p)));
+                        }
+
+                        // Opening or closing double quote
+                        self.double_quote_start = match self.double_quote_start {
+                            Some(_) => None,
+                            None => Some(abs_p),
+                        };
+                        self.byte_idx = abs_p + 1;
+                        return Ok(Some((Token::DoubleQuote, abs_p..abs_p + 1)));
                     } else if char_found == b'`' {
                         // Found backtick command substitution start
 
@@ -179,6 +332,27 @@ impl<'a> Scanner<'a> {
         }
     }
 
+    /// Check for a tilde prefix at `idx`: the tilde must start a word, follow `:`
+    /// or follow the `=` of an assignment like `PATH=~/bin`, and be followed by
+    /// `+`, `-` or a user name up to `/`, `:` or whitespace.
+    fn tilde_prefix(&self, idx: usize) -> Option<&'a str> {
+        if self.double_quote_start.is_some() {
+            return None;
+        }
+        let before = &self.source[..idx];
+        let word_start = before.chars().next_back().is_none_or(|c| c == ':' || c.is_whitespace());
+        if !word_start && !before.strip_suffix('=').is_some_and(is_assignment_name) {
+            return None;
+        }
+
+        let rest = &self.source[idx + 1..];
+        let len = rest.find(|c: char| c == '/' || c == ':' || c.is_whitespace()).unwrap_or(rest.len());
+        let user = &rest[..len];
+        let valid = matches!(user, "+" | "-")
+            || user.chars().all(|c| c.is_alphanumeric() || matches!(c, '_' | '.' | '-'));
+        valid.then_some(user)
+    }
+
     fn parse_variable(&mut self, start_idx: usize) -> Result<Option<Token<'a>>, Error> {
         // We are at '$'
         let mut iter = self.source[start_idx..].chars();
@@ -189,11 +363,24 @@ impl<'a> Scanner<'a> {
                 self.parse_braced_variable(start_idx)
             },
             Some('(') => {
-                self.parse_command_substitution(start_idx)
+                match self.parse_arithmetic(start_idx) {
+                    Some(token) => Ok(Some(token)),
+                    None => self.parse_command_substitution(start_idx),
+                }
+            },
+            Some('\'') if self.double_quote_start.is_none() => {
+                self.parse_ansi_c_quote(start_idx)
             },
             Some(c) if c.is_alphabetic() || c == '_' => {
                 self.parse_simple_variable(start_idx)
             },
+            // Positional parameters take a single digit, ${10} needs braces.
+            // `$$`, `$?`, `$!` and `$-` are special parameters.
+            Some('0'..='9' | '@' | '*' | '#' | '$' | '?' | '!' | '-') => {
+                self.byte_idx = start_idx + 2;
+                let name = &self.source[start_idx + 1..start_idx + 2];
+                Ok(Some(Token::Variable { name, indirect: false, op: None }))
+            },
             _ => {
                 // Not a variable, return literal '$'
                 self.byte_idx = start_idx + 1;
@@ -254,6 +441,59 @@ impl<'a> Scanner<'a> {
         Ok(Some(Token::Command(cmd)))
     }
 
+    /// Parse `$((expr))`. Returns `None` if the parentheses don't close with `))`,
+    /// e.g. a command starting with a subshell like `$((cd dir) && ls)`.
+    fn parse_arithmetic(&mut self, start_idx: usize) -> Option<Token<'a>> {
+        if !self.source[start_idx..].starts_with("$((") {
+            return None;
+        }
+        let inner_start = start_idx + 3; // skip '$(('
+        let remaining = &self.source[inner_start..];
+
+        let mut depth = 0;
+        for (i, c) in remaining.char_indices() {
+            match c {
+                '(' => depth += 1,
+                ')' if depth > 0 => depth -= 1,
+                ')' => {
+                    if !remaining[i + 1..].starts_with(')') {
+                        return None;
+                    }
+                    let end_idx = inner_start + i;
+                    self.byte_idx = end_idx + 2; // skip '))'
+                    return Some(Token::Arithmetic(&self.source[inner_start..end_idx]));
+                },
+                _ => {}
+            }
+        }
+        None
+    }
+
+    fn parse_ansi_c_quote(&mut self, start_idx: usize) -> Result<Option<Token<'a>>, Error> {
+        // $'...'
+        let inner_start = start_idx + 2; // skip "$'"
+        let remaining = &self.source[inner_start..];
+
+        let mut chars = remaining.char_indices();
+        while let Some((i, c)) = chars.next() {
+            match c {
+                '\\' => {
+                    chars.next(); // \' does not close the quote
+                },
+                '\'' => {
+                    let end_idx = inner_start + i;
+                    self.byte_idx = end_idx + 1; // skip closing quote
+                    return Ok(Some(Token::AnsiCQuoted(&self.source[inner_start..end_idx])));
+                },
+                _ => {}
+            }
+        }
+
+        // Unterminated: the `$` is literal, and the `'` is scanned as usual
+        self.byte_idx = start_idx + 1;
+        Ok(Some(Token::Literal(&self.source[start_idx..start_idx + 1])))
+    }
+
     fn parse_backtick_command(&mut self, start_idx: usize) -> Result<Option<Token<'a>>, Error> {
         // `command`
         let inner_start = start_idx + 1; // skip opening backtick
@@ -268,11 +508,9 @@ impl<'a> Scanner<'a> {
                 '\\' => {
                     // Handle escapes inside backticks
                     // Only \` and \\ are meaningful escapes inside backticks
-                    if let Some((_, next_c)) = chars.next() {
-                        match next_c {
-                            '`' | '\\' => continue, // escaped, skip
-                            _ => {} // pass through (backslash consumed with next char)
-                        }
+                    // Any other escaped char passes through (backslash consumed with next char)
+                    if let Some((_, '`' | '\\')) = chars.next() {
+                        continue; // escaped, skip
                     }
                 }
                 '`' => {
@@ -313,12 +551,7 @@ impl<'a> Scanner<'a> {
         let name = &self.source[start_idx+1..start_idx+len];
         self.byte_idx = start_idx + len;
         
-        Ok(Some(Token::Variable {
-            name,
-            default: None,
-            strict: false,
-            conditional: false,
-        }))
+        Ok(Some(Token::Variable { name, indirect: false, op: None }))
     }
 
     fn parse_braced_variable(&mut self, start_idx: usize) -> Result<Option<Token<'a>>, Error> {
@@ -347,51 +580,207 @@ impl<'a> Scanner<'a> {
         let content = &self.source[inner_start..end_idx];
         self.byte_idx = end_idx + 1; // skip '}'
 
+        // ${#} is the number of positional parameters, and the special
+        // parameters would otherwise parse as operators
+        if matches!(content, "#" | "$" | "?" | "!" | "-") {
+            return Ok(Some(Token::Variable { name: content, indirect: false, op: None }));
+        }
+
+        // A special parameter followed by an operator, like ${?:-0} or ${#:-0}.
+        // Not ${##} or ${!?}, the length of or indirection through one.
+        let special_op = match content.split_at(content.chars().next().map_or(0, char::len_utf8)) {
+            (first @ ("#" | "$" | "?" | "!" | "-"), rest) => {
+                rest.starts_with([':', '-', '+', '?', '=', '#', '%', '/', '^', ','])
+                    && !(matches!(first, "#" | "!") && matches!(rest, "#" | "$" | "?" | "!" | "-" | "@" | "*"))
+            },
+            _ => false,
+        };
+
+        // ${#VAR} takes no further modifiers
+        if let Some(name) = content.strip_prefix('#').filter(|n| !n.is_empty() && !special_op) {
+            return Ok(Some(Token::Variable { name, indirect: false, op: Some(Operator::Length) }));
+        }
+
+        let (indirect, content) = match content.strip_prefix('!') {
+            Some(rest) if !rest.is_empty() && !special_op => (true, rest),
+            _ => (false, content),
+        };
+
+        if indirect {
+            // ${!PREFIX*} and ${!PREFIX@}
+            if let Some(prefix) = content.strip_suffix(['*', '@']) {
+                if prefix.chars().all(|c| c.is_alphanumeric() || c == '_') {
+                    let op = Operator::Names { star: content.ends_with('*') };
+                    return Ok(Some(Token::Variable { name: prefix, indirect: false, op: Some(op) }));
+                }
+            }
+        }
+
         let mut name_len = content.len();
-        let mut modifier = None;
-        
+        let mut op = None;
+        let mut skip_to = if special_op { 1 } else { 0 };
+
         for (i, c) in content.char_indices() {
-            if c == ':' {
-               if let Some(next_c) = content[i+1..].chars().next() {
-                   if next_c == '-' {
-                       name_len = i;
-                       modifier = Some((true, false, i+2));
-                       break;
-                   } else if next_c == '+' {
-                       name_len = i;
-                       modifier = Some((true, true, i+2));
-                       break;
-                   }
-               }
-            } else if c == '-' {
-                name_len = i;
-                modifier = Some((false, false, i+1));
-                break;
-            } else if c == '+' {
-                name_len = i;
-                modifier = Some((false, true, i+1));
-                break;
+            if i < skip_to {
+                continue;
             }
+            let parsed = match c {
+                // Nested expansions in a dynamic name like ${CONFIG_${ENV}} belong to the name
+                '$' => {
+                    skip_to = nested_end(content, i);
+                    continue;
+                },
+                // So does an array subscript like ${ARR[i-1]}
+                '[' => {
+                    skip_to = content[i..].find(']').map_or(i, |end| i + end + 1);
+                    continue;
+                },
+                ':' => {
+                    let rest = &content[i + 1..];
+                    match rest.chars().next() {
+                        Some(op_char @ ('-' | '+' | '?' | '=')) => word_operator(op_char, &rest[1..], true),
+                        // Anything else after ':' is a substring, e.g. ${VAR:1:2} or ${VAR: -3}
+                        _ => match find_unnested(rest, ':') {
+                            Some(sep) => Operator::Substring { offset: &rest[..sep], length: Some(&rest[sep + 1..]) },
+                            None => Operator::Substring { offset: rest, length: None },
+                        },
+                    }
+                },
+                '-' | '+' | '?' | '=' => word_operator(c, &content[i + 1..], false),
+                '#' | '%' => {
+                    let rest = &content[i + 1..];
+                    let (pattern, longest) = match rest.strip_prefix(c) {
+                        Some(pattern) => (pattern, true),
+                        None => (rest, false),
+                    };
+                    if c == '#' {
+                        Operator::RemovePrefix { pattern, longest }
+                    } else {
+                        Operator::RemoveSuffix { pattern, longest }
+                    }
+                },
+                '/' => {
+                    let rest = &content[i + 1..];
+                    let (mode, rest) = match rest.chars().next() {
+                        Some('/') => (ReplaceMode::All, &rest[1..]),
+                        Some('#') => (ReplaceMode::Prefix, &rest[1..]),
+                        Some('%') => (ReplaceMode::Suffix, &rest[1..]),
+                        _ => (ReplaceMode::First, rest),
+                    };
+                    let (pattern, replacement) = match find_unnested(rest, '/') {
+                        Some(sep) => (&rest[..sep], &rest[sep + 1..]),
+                        None => (rest, ""),
+                    };
+                    Operator::Replace { pattern, replacement, mode }
+                },
+                '^' | ',' => {
+                    let rest = &content[i + 1..];
+                    let (all, pattern) = match rest.strip_prefix(c) {
+                        Some(pattern) => (true, pattern),
+                        None => (false, rest),
+                    };
+                    let mode = match (c, all) {
+                        ('^', false) => CaseMode::UpperFirst,
+                        ('^', true) => CaseMode::UpperAll,
+                        (_, false) => CaseMode::LowerFirst,
+                        (_, true) => CaseMode::LowerAll,
+                    };
+                    Operator::Case { mode, pattern }
+                },
+                _ => continue,
+            };
+            name_len = i;
+            op = Some(parsed);
+            break;
         }
-        
+
         let name = &content[0..name_len];
-        let default_val = if let Some((_, _, start)) = modifier {
-            Some(&content[start..])
-        } else {
-            None
-        };
-        
-        let (strict, conditional) = if let Some((s, c, _)) = modifier {
-            (s, c)
-        } else {
-            (false, false)
-        };
+        Ok(Some(Token::Variable { name, indirect, op }))
+    }
+}
+
+/// Check whether `name` is a positional parameter (`0`, `1`, `10`) or one of
+/// `@`, `*` and `#`, which refer to all of them.
+pub(crate) fn is_positional(name: &str) -> bool {
+    matches!(name, "@" | "*" | "#") || (!name.is_empty() && name.bytes().all(|b| b.is_ascii_digit()))
+}
+
+/// Split an array reference like `ARR[0]` into the array name and subscript.
+pub(crate) fn split_subscript(name: &str) -> Option<(&str, &str)> {
+    let (array, rest) = name.split_once('[')?;
+    Some((array, rest.strip_suffix(']')?))
+}
 
-        Ok(Some(Token::Variable {
-            name,
-            default: default_val,
-            strict,
-            conditional,
-        }))
+/// Check whether `name` is one of the special parameters `$`, `?`, `!` and `-`.
+pub(crate) fn is_special(name: &str) -> bool {
+    matches!(name, "$" | "?" | "!" | "-")
+}
+
+/// Check whether `text` ends with a variable name that starts a word, as in
+/// the assignment `NAME=value`.
+fn is_assignment_name(text: &str) -> bool {
+    let name = text.rsplit(char::is_whitespace).next().unwrap_or(text);
+    name.starts_with(|c: char| c.is_alphabetic() || c == '_') && name.chars().all(|c| c.is_alphanumeric() || c == '_')
+}
+
+/// Build the operator for a modifier that takes a word, e.g. `:-` in `${VAR:-word}`.
+fn word_operator(op_char: char, word: &str, strict: bool) -> Operator<'_> {
+    match op_char {
+        '-' => Operator::Default { word, strict },
+        '+' => Operator::Conditional { word, strict },
+        '=' => Operator::Assign { word, strict },
+        _ => Operator::Error { message: word, strict },
+    }
+}
+
+/// Find the end of the nested expansion starting with the `$` at `start`:
+/// `${...}`, `$(...)` or `$NAME`.
+fn nested_end(s: &str, start: usize) -> usize {
+    let rest = &s[start + 1..];
+    match rest.chars().next() {
+        Some(open @ ('{' | '(')) => {
+            let close = if open == '{' { '}' } else { ')' };
+            let mut depth = 0usize;
+            for (i, c) in rest.char_indices() {
+                if c == open {
+                    depth += 1;
+                } else if c == close {
+                    depth -= 1;
+                    if depth == 0 {
+                        return start + 1 + i + 1;
+                    }
+                }
+            }
+            s.len()
+        },
+        _ => {
+            let len = rest
+                .find(|c: char| !(c.is_alphanumeric() || c == '_'))
+                .unwrap_or(rest.len());
+            start + 1 + len
+        },
+    }
+}
+
+/// Find the first `target` in `s` that is not escaped or inside a nested
+/// `${...}` or `$(...)`.
+fn find_unnested(s: &str, target: char) -> Option<usize> {
+    let mut depth = 0usize;
+    let mut chars = s.char_indices().peekable();
+    while let Some((i, c)) = chars.next() {
+        match c {
+            '\\' => {
+                chars.next();
+            },
+            '$' if matches!(chars.peek(), Some((_, '{' | '('))) => {
+                chars.next();
+                depth += 1;
+            },
+            '{' | '(' if depth > 0 => depth += 1,
+            '}' | ')' if depth > 0 => depth -= 1,
+            _ if c == target && depth == 0 => return Some(i),
+            _ => {}
+        }
     }
+    None
 }
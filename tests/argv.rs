@@ -0,0 +1,77 @@
+mod common;
+use common::{create_germi, create_germi_with_config};
+use germi::Config;
+
+fn argv(germi: &germi::Germi, input: &str) -> Vec<String> {
+    germi.interpolate_argv(input).unwrap()
+}
+
+#[test]
+fn test_split_on_blanks() {
+    let germi = create_germi();
+    assert_eq!(argv(&germi, "ls  -la\t/tmp\n"), ["ls", "-la", "/tmp"]);
+    assert_eq!(argv(&germi, "   "), Vec::<String>::new());
+    assert_eq!(argv(&germi, r"touch a\ b"), ["touch", "a b"]);
+}
+
+#[test]
+fn test_quotes() {
+    let germi = create_germi();
+    assert_eq!(argv(&germi, r#"echo "a  b" 'c $TEST_VAR' "$TEST_VAR x""#), ["echo", "a  b", "c $TEST_VAR", "test_value x"]);
+    assert_eq!(argv(&germi, r#"x"y"'z' "" ''"#), ["xyz", "", ""]);
+    assert_eq!(argv(&germi, r#""say \"hi\" \$5""#), [r#"say "hi" $5"#]);
+    assert_eq!(argv(&germi, r"$'a\tb' c"), ["a\tb", "c"]);
+}
+
+#[test]
+fn test_unquoted_expansions_are_split() {
+    let mut germi = create_germi();
+    germi.add_variable("FLAGS", "  -v   --color=auto ");
+    germi.add_variable("EMPTY", "");
+    assert_eq!(argv(&germi, "ls $FLAGS dir"), ["ls", "-v", "--color=auto", "dir"]);
+    assert_eq!(argv(&germi, r#"ls "$FLAGS""#), ["ls", "  -v   --color=auto "]);
+    assert_eq!(argv(&germi, "a $EMPTY b"), ["a", "b"]);
+    assert_eq!(argv(&germi, r#"a "$EMPTY" b"#), ["a", "", "b"]);
+    assert_eq!(argv(&germi, "pre${FLAGS}post"), ["pre", "-v", "--color=auto", "post"]);
+}
+
+#[test]
+fn test_values_are_not_reparsed() {
+    let mut germi = create_germi();
+    germi.add_variable("NAME", "it's; rm -rf \"/\"");
+    assert_eq!(argv(&germi, "echo $NAME"), ["echo", "it's;", "rm", "-rf", "\"/\""]);
+    assert_eq!(argv(&germi, "echo \"$NAME\""), ["echo", "it's; rm -rf \"/\""]);
+}
+
+#[test]
+fn test_custom_ifs() {
+    let mut germi = create_germi();
+    germi.add_variable("IFS", ",");
+    germi.add_variable("LIST", "a,b,,c");
+    assert_eq!(argv(&germi, "cmd $LIST"), ["cmd", "a", "b", "", "c"]);
+
+    germi.add_variable("IFS", ", ");
+    germi.add_variable("LIST", "a, b ,c");
+    assert_eq!(argv(&germi, "cmd $LIST"), ["cmd", "a", "b", "c"]);
+
+    // Empty IFS disables splitting
+    germi.add_variable("IFS", "");
+    assert_eq!(argv(&germi, "cmd $LIST"), ["cmd", "a, b ,c"]);
+}
+
+#[test]
+fn test_argv_operators_and_errors() {
+    let germi = create_germi();
+    assert_eq!(argv(&germi, "echo ${UNSET:-a b} $((1 + 2))"), ["echo", "a", "b", "3"]);
+    assert!(germi.interpolate_argv(r#"echo "unterminated"#).is_err());
+    assert!(germi.interpolate_argv("echo ${UNSET:?required}").is_err());
+}
+
+#[test]
+fn test_argv_tilde() {
+    let mut config = Config::default();
+    config.features.tilde = true;
+    let mut germi = create_germi_with_config(config);
+    germi.add_variable("HOME", "/home/my user");
+    assert_eq!(argv(&germi, "ls ~/x \"~/y\""), ["ls", "/home/my user/x", "~/y"]);
+}
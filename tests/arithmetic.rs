@@ -0,0 +1,116 @@
+mod common;
+use common::create_germi;
+use germi::Error;
+
+#[test]
+fn test_basic_arithmetic() {
+    let germi = create_germi();
+    assert_eq!(germi.interpolate("$((1 + 2))").unwrap(), "3");
+    assert_eq!(germi.interpolate("$((7 - 10))").unwrap(), "-3");
+    assert_eq!(germi.interpolate("$((6 * 7))").unwrap(), "42");
+    assert_eq!(germi.interpolate("$((7 / 2)) $((7 % 2)) $((-7 / 2))").unwrap(), "3 1 -3");
+    assert_eq!(germi.interpolate("$((2 ** 10))").unwrap(), "1024");
+    assert_eq!(germi.interpolate("port=$((8000 + 80))").unwrap(), "port=8080");
+    assert_eq!(germi.interpolate("$(( ))").unwrap(), "0");
+}
+
+#[test]
+fn test_arithmetic_precedence() {
+    let germi = create_germi();
+    assert_eq!(germi.interpolate("$((1 + 2 * 3))").unwrap(), "7");
+    assert_eq!(germi.interpolate("$(((1 + 2) * 3))").unwrap(), "9");
+    assert_eq!(germi.interpolate("$((2 ** 3 ** 2))").unwrap(), "512");
+    assert_eq!(germi.interpolate("$((-2 ** 2))").unwrap(), "4");
+    assert_eq!(germi.interpolate("$((10 - 4 - 3))").unwrap(), "3");
+    assert_eq!(germi.interpolate("$((1 + 2 == 3 && 4 > 3))").unwrap(), "1");
+}
+
+#[test]
+fn test_arithmetic_comparisons_and_logic() {
+    let germi = create_germi();
+    assert_eq!(germi.interpolate("$((3 < 4)) $((3 >= 4)) $((3 == 3)) $((3 != 3))").unwrap(), "1 0 1 0");
+    assert_eq!(germi.interpolate("$((!0)) $((!5)) $((1 || 0)) $((1 && 0))").unwrap(), "1 0 1 0");
+    assert_eq!(germi.interpolate("$((5 > 3 ? 10 : 20))").unwrap(), "10");
+    assert_eq!(germi.interpolate("$((0 ? 1 : 0 ? 2 : 3))").unwrap(), "3");
+}
+
+#[test]
+fn test_arithmetic_bit_operations() {
+    let germi = create_germi();
+    assert_eq!(germi.interpolate("$((6 & 3)) $((6 | 3)) $((6 ^ 3)) $((~0))").unwrap(), "2 7 5 -1");
+    assert_eq!(germi.interpolate("$((1 << 4)) $((256 >> 2))").unwrap(), "16 64");
+}
+
+#[test]
+fn test_arithmetic_number_bases() {
+    let germi = create_germi();
+    assert_eq!(germi.interpolate("$((0x1F)) $((010)) $((2#1010)) $((36#z))").unwrap(), "31 8 10 35");
+}
+
+#[test]
+fn test_arithmetic_variables() {
+    let mut germi = create_germi();
+    germi.add_variable("BASE_PORT", "8000");
+    germi.add_variable("REPLICAS", "3");
+    germi.add_variable("EXPR", "REPLICAS * 2");
+    // Bare names, $VAR and ${VAR} all work
+    assert_eq!(germi.interpolate("$((BASE_PORT + REPLICAS))").unwrap(), "8003");
+    assert_eq!(germi.interpolate("$(($BASE_PORT + ${REPLICAS}))").unwrap(), "8003");
+    assert_eq!(germi.interpolate("$((${MISSING:-2} * 2))").unwrap(), "4");
+    // Values are evaluated as expressions, unset names are 0
+    assert_eq!(germi.interpolate("$((EXPR + 1))").unwrap(), "7");
+    assert_eq!(germi.interpolate("$((MISSING + 1))").unwrap(), "1");
+    assert_eq!(germi.interpolate("$((EMPTY_VAR + 1))").unwrap(), "1");
+}
+
+#[test]
+fn test_arithmetic_short_circuit() {
+    let germi = create_germi();
+    assert_eq!(germi.interpolate("$((0 && 1 / 0))").unwrap(), "0");
+    assert_eq!(germi.interpolate("$((1 || 1 / 0))").unwrap(), "1");
+    assert_eq!(germi.interpolate("$((1 ? 2 : 1 / 0))").unwrap(), "2");
+}
+
+#[test]
+fn test_arithmetic_errors() {
+    let mut germi = create_germi();
+    germi.add_variable("LOOP", "LOOP + 1");
+    assert!(matches!(germi.interpolate("$((1 / 0))"), Err(Error::ArithmeticError(_, 5))));
+    assert!(matches!(germi.interpolate("x $((1 +))"), Err(Error::ArithmeticError(_, _))));
+    assert!(matches!(germi.interpolate("$((1 2))"), Err(Error::ArithmeticError(_, 5))));
+    assert!(matches!(germi.interpolate("$((2 ** -1))"), Err(Error::ArithmeticError(_, _))));
+    assert!(matches!(germi.interpolate("$((08))"), Err(Error::ArithmeticError(_, _))));
+    assert!(matches!(germi.interpolate("$((LOOP))"), Err(Error::RecursiveLookup(_))));
+}
+
+#[test]
+fn test_arithmetic_nesting_limit() {
+    let germi = create_germi();
+    let deep = format!("$(({}1{}))", "(".repeat(100_000), ")".repeat(100_000));
+    assert!(matches!(germi.interpolate(&deep), Err(Error::ArithmeticError(_, _))));
+    for expr in ["-".repeat(100_000) + "1", "2**".repeat(100_000) + "1", "1?".repeat(100_000) + "1" + &":1".repeat(100_000)] {
+        assert!(matches!(germi.interpolate(&format!("$(({}))", expr)), Err(Error::ArithmeticError(_, _))));
+    }
+
+    let nested = format!("$(({}1{}))", "(".repeat(100), ")".repeat(100));
+    assert_eq!(germi.interpolate(&nested).unwrap(), "1");
+}
+
+#[test]
+fn test_arithmetic_is_not_a_command() {
+    let germi = create_germi();
+    // A subshell inside a command substitution is still a command
+    let result = germi.interpolate("$((echo a) | cat)").unwrap();
+    assert_eq!(result, "$((echo a) | cat)");
+}
+
+#[test]
+fn test_arithmetic_disabled() {
+    use germi::Config;
+    let mut config = Config::default();
+    config.features.arithmetic = false;
+    let germi = common::create_germi_with_config(config);
+
+    let result = germi.interpolate("$((1 + 2))").unwrap();
+    assert_eq!(result, "$((1 + 2))");
+}
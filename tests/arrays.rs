@@ -0,0 +1,79 @@
+mod common;
+use common::create_germi;
+use germi::Error;
+
+fn create_germi_with_regions() -> germi::Germi {
+    let mut germi = create_germi();
+    germi.add_array("REGIONS", ["us-east-1", "eu-west-1", "ap-south-1"]);
+    germi
+}
+
+#[test]
+fn test_array_indexing() {
+    let mut germi = create_germi_with_regions();
+    germi.add_variable("I", "1");
+    assert_eq!(germi.interpolate("${REGIONS[0]} ${REGIONS[2]}").unwrap(), "us-east-1 ap-south-1");
+    assert_eq!(germi.interpolate("${REGIONS[-1]} ${REGIONS[I]} ${REGIONS[$I+1]}").unwrap(), "ap-south-1 eu-west-1 ap-south-1");
+    // $ARR is the first element
+    assert_eq!(germi.interpolate("$REGIONS ${REGIONS}").unwrap(), "us-east-1 us-east-1");
+    assert_eq!(germi.interpolate("${REGIONS[5]:-none}").unwrap(), "none");
+    assert!(matches!(germi.interpolate("${REGIONS[5]}"), Err(Error::MissingVar(_))));
+}
+
+#[test]
+fn test_array_all_elements() {
+    let mut germi = create_germi_with_regions();
+    assert_eq!(germi.interpolate("${REGIONS[@]}").unwrap(), "us-east-1 eu-west-1 ap-south-1");
+    germi.add_variable("IFS", ",");
+    assert_eq!(germi.interpolate("${REGIONS[*]}").unwrap(), "us-east-1,eu-west-1,ap-south-1");
+    assert_eq!(germi.interpolate("${UNSET[@]}|${UNSET[@]:-none}").unwrap(), "|none");
+}
+
+#[test]
+fn test_array_length_and_slicing() {
+    let germi = create_germi_with_regions();
+    assert_eq!(germi.interpolate("${#REGIONS[@]} ${#REGIONS[*]} ${#REGIONS[1]}").unwrap(), "3 3 9");
+    assert_eq!(germi.interpolate("${REGIONS[@]:1}").unwrap(), "eu-west-1 ap-south-1");
+    assert_eq!(germi.interpolate("${REGIONS[@]:0:2}").unwrap(), "us-east-1 eu-west-1");
+    assert_eq!(germi.interpolate("${REGIONS[@]: -1}").unwrap(), "ap-south-1");
+}
+
+#[test]
+fn test_array_operators_per_element() {
+    let germi = create_germi_with_regions();
+    assert_eq!(germi.interpolate("${REGIONS[@]%-1}").unwrap(), "us-east eu-west ap-south");
+    assert_eq!(germi.interpolate("${REGIONS[@]/#/--region=}").unwrap(), "--region=us-east-1 --region=eu-west-1 --region=ap-south-1");
+    assert_eq!(germi.interpolate("${REGIONS[@]^^}").unwrap(), "US-EAST-1 EU-WEST-1 AP-SOUTH-1");
+}
+
+#[test]
+fn test_scalar_as_array() {
+    let germi = create_germi();
+    assert_eq!(germi.interpolate("${TEST_VAR[0]} ${TEST_VAR[@]} ${#TEST_VAR[@]}").unwrap(), "test_value test_value 1");
+    assert!(germi.interpolate("${TEST_VAR[1]}").is_err());
+}
+
+#[test]
+fn test_array_elements_are_interpolated() {
+    let mut germi = create_germi();
+    germi.add_array("LIST", ["$TEST_VAR", "${NESTED_VAR}!"]);
+    assert_eq!(germi.interpolate("${LIST[@]}").unwrap(), "test_value test_value!");
+    assert_eq!(germi.interpolate("${LIST[1]}").unwrap(), "test_value!");
+}
+
+#[test]
+fn test_array_assign_is_error() {
+    let germi = create_germi_with_regions();
+    assert!(matches!(germi.interpolate("${REGIONS[5]:=x}"), Err(Error::SyntaxError(_, 0))));
+    assert!(matches!(germi.interpolate("${REGIONS[@]:=x}"), Err(Error::SyntaxError(_, 0))));
+}
+
+#[test]
+fn test_array_argv() {
+    let mut germi = create_germi();
+    germi.add_array("FILES", ["a b.txt", "c.txt"]);
+    germi.add_array("NONE", Vec::<String>::new());
+    assert_eq!(germi.interpolate_argv(r#"rm "${FILES[@]}" "${NONE[@]}""#).unwrap(), ["rm", "a b.txt", "c.txt"]);
+    assert_eq!(germi.interpolate_argv("rm ${FILES[@]}").unwrap(), ["rm", "a", "b.txt", "c.txt"]);
+    assert_eq!(germi.interpolate_argv(r#"echo "${FILES[*]}""#).unwrap(), ["echo", "a b.txt c.txt"]);
+}
@@ -0,0 +1,87 @@
+mod common;
+use common::create_germi;
+use germi::Error;
+
+fn expand(input: &str) -> Vec<String> {
+    create_germi().expand_words(input).unwrap()
+}
+
+#[test]
+fn test_comma_lists() {
+    assert_eq!(expand("host-{a,b,c}.local"), ["host-a.local", "host-b.local", "host-c.local"]);
+    assert_eq!(expand("{a,b}{1,2}"), ["a1", "a2", "b1", "b2"]);
+    assert_eq!(expand("x{,-dev}"), ["x", "x-dev"]);
+}
+
+#[test]
+fn test_nested() {
+    assert_eq!(expand("{a,b{1,2},c}"), ["a", "b1", "b2", "c"]);
+    assert_eq!(expand("/usr/{ucb/{ex,edit},lib/{ex?.?*,how_ex}}"), [
+        "/usr/ucb/ex",
+        "/usr/ucb/edit",
+        "/usr/lib/ex?.?*",
+        "/usr/lib/how_ex",
+    ]);
+}
+
+#[test]
+fn test_numeric_ranges() {
+    assert_eq!(expand("shard-{1..4}"), ["shard-1", "shard-2", "shard-3", "shard-4"]);
+    assert_eq!(expand("{3..1}"), ["3", "2", "1"]);
+    assert_eq!(expand("{-1..1}"), ["-1", "0", "1"]);
+    assert_eq!(expand("{0..10..5}"), ["0", "5", "10"]);
+    assert_eq!(expand("{10..0..-4}"), ["10", "6", "2"]);
+}
+
+#[test]
+fn test_zero_padding() {
+    assert_eq!(expand("web{08..11}"), ["web08", "web09", "web10", "web11"]);
+    assert_eq!(expand("{1..003}"), ["001", "002", "003"]);
+    assert_eq!(expand("{-02..1}"), ["-02", "-01", "000", "001"]);
+}
+
+#[test]
+fn test_alpha_ranges() {
+    assert_eq!(expand("{a..e}"), ["a", "b", "c", "d", "e"]);
+    assert_eq!(expand("{e..a..2}"), ["e", "c", "a"]);
+    assert_eq!(expand("{A..C}{1..2}"), ["A1", "A2", "B1", "B2", "C1", "C2"]);
+}
+
+#[test]
+fn test_invalid_braces_are_literal() {
+    assert_eq!(expand("{a}"), ["{a}"]);
+    assert_eq!(expand("{}"), ["{}"]);
+    assert_eq!(expand("{a..}"), ["{a..}"]);
+    assert_eq!(expand("{1..b}"), ["{1..b}"]);
+    assert_eq!(expand("{Z..a}"), ["{Z..a}"]);
+    assert_eq!(expand("{a{b,c}"), ["{ab", "{ac"]);
+    assert_eq!(expand(r"\{a,b}"), ["{a,b}"]);
+    assert_eq!(expand("'{a,b}'"), ["'{a,b}'"]);
+}
+
+#[test]
+fn test_with_interpolation() {
+    assert_eq!(expand("${TEST_VAR}-{1..2}"), ["test_value-1", "test_value-2"]);
+    assert_eq!(expand("{$TEST_VAR,none}"), ["test_value", "none"]);
+    // Braces and commas inside parameter expansions belong to them
+    assert_eq!(expand("${UNSET:-a,b}"), ["a,b"]);
+    assert_eq!(expand("${UNSET:-{x,y}}{1,2}"), ["{x,y}1", "{x,y}2"]);
+}
+
+#[test]
+fn test_expand_words_errors() {
+    let germi = create_germi();
+    assert!(germi.expand_words("{a,${UNSET:?missing}}").is_err());
+}
+
+#[test]
+fn test_too_many_words() {
+    let germi = create_germi();
+    assert!(matches!(
+        germi.expand_words("{-9223372036854775808..9223372036854775807}"),
+        Err(Error::SyntaxError(_, 0))
+    ));
+    assert!(matches!(germi.expand_words("x {1..100000000000}"), Err(Error::SyntaxError(_, 2))));
+    assert!(matches!(germi.expand_words("{1..1000}{1..1000}"), Err(Error::SyntaxError(_, 0))));
+    assert_eq!(expand("{1..100000}").len(), 100_000);
+}
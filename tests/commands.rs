@@ -41,3 +41,78 @@ async fn test_commands_disabled() {
     let result = germi.interpolate_async("$(echo hi)").await.unwrap();
     assert_eq!(result, "$(echo hi)");
 }
+
+#[tokio::test]
+async fn test_ansi_c_quote_is_not_executed() {
+    let germi = create_germi();
+    let result = germi.interpolate_async(r"$'\x24(echo hi)'").await.unwrap();
+    assert_eq!(result, "$(echo hi)");
+}
+
+#[tokio::test]
+async fn test_command_in_double_quotes() {
+    use germi::Config;
+    let mut config = Config::default();
+    config.features.double_quotes = true;
+    let germi = common::create_germi_with_config(config);
+
+    let result = germi.interpolate_async(r#""[$(echo hi)] \"\$(echo no)\"""#).await.unwrap();
+    assert_eq!(result, r#"[hi] "$(echo no)""#);
+}
+
+#[tokio::test]
+async fn test_stripped_single_quotes_are_not_executed() {
+    use germi::Config;
+    let germi = common::create_germi_with_config(Config {
+        strip_single_quotes: true,
+        ..Config::default()
+    });
+
+    let result = germi.interpolate_async("'$(echo no)' $(echo hi)").await.unwrap();
+    assert_eq!(result, "$(echo no) hi");
+}
+
+#[tokio::test]
+async fn test_exit_status_from_provider() {
+    let mut germi = create_germi();
+    germi.add_variable("?", "7");
+    // Commands do not change $?, it always comes from the provider
+    let result = germi.interpolate_async("$? $(true) $? ${?}").await.unwrap();
+    assert_eq!(result, "7  7 7");
+    let result = germi.interpolate_async("$(echo $?)").await.unwrap();
+    assert_eq!(result, "7");
+    assert!(matches!(germi.interpolate_async("$(false) $?").await, Err(Error::CommandError(_))));
+}
+
+#[tokio::test]
+async fn test_exit_status_with_commands_disabled() {
+    use germi::Config;
+    let mut config = Config::default();
+    config.features.commands = false;
+    config.features.backtick_commands = false;
+    let mut germi = common::create_germi_with_config(config);
+    germi.add_variable("?", "7");
+
+    let result = germi.interpolate_async("status $? ${?:-0}").await.unwrap();
+    assert_eq!(result, "status 7 7");
+    assert_eq!(result, germi.interpolate("status $? ${?:-0}").unwrap());
+}
+
+#[tokio::test]
+async fn test_single_quote_in_double_quotes() {
+    use germi::Config;
+    let mut config = Config::default();
+    config.features.double_quotes = true;
+    let germi = common::create_germi_with_config(config);
+
+    let result = germi.interpolate_async("\"it's $(echo hi)\" 'it''s'").await.unwrap();
+    assert_eq!(result, "it's hi 'it''s'");
+
+    let mut config = Config::default();
+    config.features.double_quotes = true;
+    config.features.commands = false;
+    config.features.backtick_commands = false;
+    let germi = common::create_germi_with_config(config);
+    let result = germi.interpolate_async("\"it's $(echo hi)\"").await.unwrap();
+    assert_eq!(result, "it's $(echo hi)");
+}
@@ -131,3 +131,69 @@ fn test_lazy_evaluation_check() {
     let res2 = germi.interpolate("${MISSING:+${MISSING}}").unwrap();
     assert_eq!(res2, "");
 }
+
+#[test]
+fn test_dynamic_variable_name() {
+    let mut germi = create_germi();
+    germi.add_variable("ENV", "prod");
+    germi.add_variable("CONFIG_prod", "prod-config");
+    germi.add_variable("CONFIG_dev", "dev-config");
+
+    let res = germi.interpolate("${CONFIG_${ENV}}").unwrap();
+    assert_eq!(res, "prod-config");
+
+    let res = germi.interpolate("${CONFIG_$ENV}").unwrap();
+    assert_eq!(res, "prod-config");
+}
+
+#[test]
+fn test_dynamic_variable_name_with_modifiers() {
+    let mut germi = create_germi();
+    germi.add_variable("ENV", "prod");
+    germi.add_variable("CONFIG_prod", "prod-config");
+
+    // Modifiers inside the nested name belong to the nested expansion
+    let res = germi.interpolate("${CONFIG_${STAGE:-prod}}").unwrap();
+    assert_eq!(res, "prod-config");
+
+    let res = germi.interpolate("${CONFIG_${ENV}:-fallback}").unwrap();
+    assert_eq!(res, "prod-config");
+
+    let res = germi.interpolate("${CONFIG_${STAGE:-qa}:-fallback}").unwrap();
+    assert_eq!(res, "fallback");
+
+    let res = germi.interpolate("${#CONFIG_${ENV}} ${CONFIG_${ENV}%-config}").unwrap();
+    assert_eq!(res, "11 prod");
+}
+
+#[test]
+fn test_dynamic_variable_name_missing() {
+    let mut germi = create_germi();
+    germi.add_variable("ENV", "staging");
+
+    let res = germi.interpolate("${CONFIG_${ENV}}");
+    assert_eq!(res, Err(Error::MissingVar("CONFIG_staging".into())));
+
+    let res = germi.interpolate("${CONFIG_${MISSING}}");
+    assert_eq!(res, Err(Error::MissingVar("MISSING".into())));
+}
+
+#[test]
+fn test_dynamic_variable_name_depth_limit() {
+    use germi::Config;
+    let config = Config {
+        max_depth: 3,
+        ..Config::default()
+    };
+    let mut germi = common::create_germi_with_config(config);
+    germi.add_variable("A", "B");
+    germi.add_variable("B", "C");
+    germi.add_variable("C", "D");
+    germi.add_variable("D", "done");
+
+    let res = germi.interpolate("${${${A}}}").unwrap();
+    assert_eq!(res, "D");
+
+    let res = germi.interpolate("${${${${${A}}}}}");
+    assert!(matches!(res, Err(Error::RecursiveLookup(_))));
+}
@@ -0,0 +1,49 @@
+use germi::{EnvProvider, Germi};
+
+// Each test uses its own variables, as tests run in parallel
+
+#[test]
+fn test_live_sees_later_changes() {
+    let germi = Germi::from_env();
+    std::env::set_var("GERMI_TEST_LIVE", "one");
+    assert_eq!(germi.interpolate("${GERMI_TEST_LIVE}").unwrap(), "one");
+    std::env::set_var("GERMI_TEST_LIVE", "two");
+    assert_eq!(germi.interpolate("${GERMI_TEST_LIVE}").unwrap(), "two");
+    assert_eq!(germi.interpolate("${!GERMI_TEST_LI*}").unwrap(), "GERMI_TEST_LIVE");
+    std::env::remove_var("GERMI_TEST_LIVE");
+    assert_eq!(germi.interpolate("${GERMI_TEST_LIVE:-gone}").unwrap(), "gone");
+}
+
+#[test]
+fn test_snapshot_is_fixed() {
+    std::env::set_var("GERMI_TEST_SNAPSHOT", "before");
+    let germi = Germi::with_provider(EnvProvider::snapshot());
+    std::env::set_var("GERMI_TEST_SNAPSHOT", "after");
+    assert_eq!(germi.interpolate("$GERMI_TEST_SNAPSHOT").unwrap(), "before");
+}
+
+#[test]
+fn test_strip_prefix() {
+    std::env::set_var("GERMI_STRIP_PORT", "8080");
+    for provider in [EnvProvider::snapshot(), EnvProvider::live()] {
+        let germi = Germi::with_provider(provider.strip_prefix("GERMI_STRIP_"));
+        assert_eq!(germi.interpolate("port=${PORT}").unwrap(), "port=8080");
+        assert!(germi.interpolate("${GERMI_STRIP_PORT}").is_err());
+    }
+    for provider in [EnvProvider::snapshot(), EnvProvider::live()] {
+        let germi = Germi::with_provider(provider.strip_prefix("GERMI_STRIP_"));
+        assert_eq!(germi.interpolate("${!PO*}").unwrap(), "PORT");
+    }
+}
+
+#[test]
+fn test_filter_prefix() {
+    std::env::set_var("GERMI_FILTER_HOST", "db");
+    std::env::set_var("GERMI_OTHER_HOST", "cache");
+    for provider in [EnvProvider::snapshot(), EnvProvider::live()] {
+        let germi = Germi::with_provider(provider.filter_prefix("GERMI_FILTER_"));
+        assert_eq!(germi.interpolate("$GERMI_FILTER_HOST ${GERMI_OTHER_HOST:-none}").unwrap(), "db none");
+    }
+    let germi = Germi::with_provider(EnvProvider::snapshot().filter_prefix("GERMI_FILTER_"));
+    assert_eq!(germi.interpolate("${!GERMI_*}").unwrap(), "GERMI_FILTER_HOST");
+}
@@ -28,3 +28,158 @@ fn test_escapes_disabled() {
     let result = germi.interpolate(r#"Line1\nLine2"#).unwrap();
     assert_eq!(result, r#"Line1\nLine2"#); // Literal backslash
 }
+
+#[test]
+fn test_ansi_c_quoting() {
+    let germi = create_germi();
+    assert_eq!(germi.interpolate(r"$'a\nb\tc'").unwrap(), "a\nb\tc");
+    assert_eq!(germi.interpolate(r"$'\a\b\e\E\f\r\v'").unwrap(), "\x07\x08\x1b\x1b\x0c\r\x0b");
+    assert_eq!(germi.interpolate(r#"$'it\'s \"quoted\" \\ \?'"#).unwrap(), r#"it's "quoted" \ ?"#);
+    assert_eq!(germi.interpolate(r"x=$'1\n2' y").unwrap(), "x=1\n2 y");
+}
+
+#[test]
+fn test_ansi_c_numeric_escapes() {
+    let germi = create_germi();
+    assert_eq!(germi.interpolate(r"$'\x41\x7e'").unwrap(), "A~");
+    assert_eq!(germi.interpolate(r"$'\u00e9\u263A'").unwrap(), "é☺");
+    assert_eq!(germi.interpolate(r"$'\U0001F680'").unwrap(), "🚀");
+    assert_eq!(germi.interpolate(r"$'\0101\101\0'").unwrap(), "AA\0");
+    assert_eq!(germi.interpolate(r"$'\x4g'").unwrap(), "\x04g");
+}
+
+#[test]
+fn test_ansi_c_control_chars() {
+    let germi = create_germi();
+    assert_eq!(germi.interpolate(r"$'\cA\ca\c['").unwrap(), "\x01\x01\x1b");
+}
+
+#[test]
+fn test_ansi_c_literal_content() {
+    let germi = create_germi();
+    // Unknown escapes are kept, and variables are not expanded
+    assert_eq!(germi.interpolate(r"$'\z ${TEST_VAR}'").unwrap(), r"\z ${TEST_VAR}");
+    assert_eq!(germi.interpolate(r"$'\x'").unwrap(), r"\x");
+}
+
+#[test]
+fn test_ansi_c_errors() {
+    use germi::Error;
+    let germi = create_germi();
+    assert!(matches!(germi.interpolate(r"$'\UFFFFFFFF'"), Err(Error::SyntaxError(_, 2))));
+    assert!(matches!(germi.interpolate(r"$'\uD800'"), Err(Error::SyntaxError(_, _))));
+}
+
+#[test]
+fn test_ansi_c_quotes_disabled() {
+    use germi::Config;
+    let mut config = Config::default();
+    config.features.ansi_c_quotes = false;
+    let germi = common::create_germi_with_config(config);
+
+    // Plain text, so only the regular escapes apply
+    let result = germi.interpolate(r"$'a\x41'").unwrap();
+    assert_eq!(result, r"$'aA'");
+    assert_eq!(germi.interpolate("cost $'5").unwrap(), "cost $'5");
+}
+
+#[test]
+fn test_ansi_c_unterminated() {
+    let germi = create_germi();
+    // The `$` is literal, as is the unterminated single quote
+    assert_eq!(germi.interpolate("ab $'unterminated $TEST_VAR").unwrap(), "ab $'unterminated $TEST_VAR");
+    assert_eq!(germi.interpolate("cost $'5").unwrap(), "cost $'5");
+}
+
+#[test]
+fn test_hex_and_unicode_escapes() {
+    let germi = create_germi();
+    assert_eq!(germi.interpolate(r"\x41\x7e-\x4").unwrap(), "A~-\x04");
+    assert_eq!(germi.interpolate(r"\u00e9 \u{1F600} \u{41}").unwrap(), "é 😀 A");
+    assert_eq!(germi.interpolate(r"\0\a\b\e\f\v").unwrap(), "\0\x07\x08\x1b\x0c\x0b");
+    assert_eq!(germi.interpolate(r"${TEST_VAR}\x21").unwrap(), "test_value!");
+}
+
+#[test]
+fn test_malformed_escapes() {
+    use germi::Error;
+    let germi = create_germi();
+    assert!(matches!(germi.interpolate(r"${TEST_VAR} \u{41"), Err(Error::SyntaxError(_, 12))));
+    assert!(matches!(germi.interpolate(r"\u{110000}"), Err(Error::SyntaxError(_, 0))));
+    assert!(matches!(germi.interpolate(r"\u{41"), Err(Error::SyntaxError(_, 0))));
+    assert!(matches!(germi.interpolate(r"\uD800"), Err(Error::SyntaxError(_, 0))));
+}
+
+#[test]
+fn test_hex_and_unicode_escapes_without_digits() {
+    let mut germi = create_germi();
+    // Kept as-is, like $'...' in bash
+    assert_eq!(germi.interpolate(r"ab\xZZ \u \x").unwrap(), r"ab\xZZ \u \x");
+    assert_eq!(germi.interpolate(r"C:\users 'C:\users'").unwrap(), r"C:\users 'C:\users'");
+    // Fewer digits than the maximum are fine too
+    assert_eq!(germi.interpolate(r"\u41 \x7").unwrap(), "A \x07");
+
+    germi.add_variable("DIR", r"C:\users");
+    assert_eq!(germi.interpolate("$DIR").unwrap(), r"C:\users");
+}
+
+#[test]
+fn test_malformed_escapes_in_values() {
+    use germi::Error;
+    let mut germi = create_germi();
+    germi.add_variable("P", r"C:\uD800");
+    // Reported at the expression, not inside the value
+    assert!(matches!(germi.interpolate("$P"), Err(Error::SyntaxError(_, 0))));
+    assert!(matches!(germi.interpolate("dir: ${P}"), Err(Error::SyntaxError(_, 5))));
+    assert!(matches!(germi.interpolate(r"x ${UNSET:-\uD800}"), Err(Error::SyntaxError(_, 2))));
+    assert!(matches!(germi.interpolate_argv("ls $P"), Err(Error::SyntaxError(_, 3))));
+
+    let mut germi = common::create_germi_with_config(germi::Config {
+        escape_style: germi::EscapeStyle::None,
+        ..germi::Config::default()
+    });
+    germi.add_variable("P", r"C:\users\bob");
+    assert_eq!(germi.interpolate("$P").unwrap(), r"C:\users\bob");
+}
+
+#[test]
+fn test_json_escape_style() {
+    use germi::{Config, Error, EscapeStyle};
+    let germi = common::create_germi_with_config(Config {
+        escape_style: EscapeStyle::Json,
+        ..Config::default()
+    });
+
+    assert_eq!(germi.interpolate(r#"\"a\/b\"\n\u00e9"#).unwrap(), "\"a/b\"\né");
+    assert_eq!(germi.interpolate(r"\ud83d\ude00").unwrap(), "😀");
+    assert!(matches!(germi.interpolate(r"ok \ud83d"), Err(Error::SyntaxError(_, 3))));
+    assert!(matches!(germi.interpolate(r"\x41"), Err(Error::SyntaxError(_, 0))));
+    assert!(matches!(germi.interpolate(r"\u{41}"), Err(Error::SyntaxError(_, 0))));
+}
+
+#[test]
+fn test_rust_escape_style() {
+    use germi::{Config, Error, EscapeStyle};
+    let germi = common::create_germi_with_config(Config {
+        escape_style: EscapeStyle::Rust,
+        ..Config::default()
+    });
+
+    assert_eq!(germi.interpolate(r"\x41\0\u{e9}\'").unwrap(), "A\0é'");
+    assert!(matches!(germi.interpolate(r"\x80"), Err(Error::SyntaxError(_, 0))));
+    assert!(matches!(germi.interpolate(r"\u00e9"), Err(Error::SyntaxError(_, 0))));
+    assert!(matches!(germi.interpolate(r"a\q"), Err(Error::SyntaxError(_, 1))));
+}
+
+#[test]
+fn test_no_escape_style() {
+    use germi::{Config, EscapeStyle};
+    let germi = common::create_germi_with_config(Config {
+        escape_style: EscapeStyle::None,
+        ..Config::default()
+    });
+
+    assert_eq!(germi.interpolate(r"a\nb\x41").unwrap(), r"a\nb\x41");
+    // Escaped dollars are still handled by the scanner
+    assert_eq!(germi.interpolate(r"\${TEST_VAR}").unwrap(), "${TEST_VAR}");
+}
@@ -134,3 +134,155 @@ fn test_matrix_empty_loose_conditional() { // ${VAR+rep} where VAR="" -> "rep" (
     let res = germi.interpolate("${EMPTY_VAR+rep}").unwrap();
     assert_eq!(res, "rep");
 }
+
+#[test]
+fn test_error_expansion() {
+    use germi::Error;
+    let germi = create_germi();
+    // ${VAR:?message} - fail with message if unset or empty
+    let result = germi.interpolate("${TEST_VAR:?must be set}").unwrap();
+    assert_eq!(result, "test_value");
+
+    let result = germi.interpolate("${MISSING:?must be set}");
+    assert_eq!(result, Err(Error::RequiredVar("MISSING".into(), "must be set".into())));
+
+    let result = germi.interpolate("${EMPTY_VAR:?must be set}");
+    assert_eq!(result, Err(Error::RequiredVar("EMPTY_VAR".into(), "must be set".into())));
+}
+
+#[test]
+fn test_loose_error_expansion() {
+    use germi::Error;
+    let germi = create_germi();
+    // ${VAR?message} - fail only if unset (empty is valid value)
+    let result = germi.interpolate("${EMPTY_VAR?must be set}").unwrap();
+    assert_eq!(result, "");
+
+    let result = germi.interpolate("${MISSING?}");
+    assert_eq!(result, Err(Error::RequiredVar("MISSING".into(), "".into())));
+    assert_eq!(result.unwrap_err().to_string(), "MISSING: parameter null or not set");
+}
+
+#[test]
+fn test_error_message_is_interpolated() {
+    use germi::Error;
+    let germi = create_germi();
+    let result = germi.interpolate("${MISSING:?expected like ${TEST_VAR}}");
+    assert_eq!(result, Err(Error::RequiredVar("MISSING".into(), "expected like test_value".into())));
+}
+
+#[test]
+fn test_strict_unsets() {
+    use germi::{Config, Error};
+    let config = Config {
+        strict_unsets: true,
+        ..Config::default()
+    };
+    let germi = common::create_germi_with_config(config);
+
+    let result = germi.interpolate("${EMPTY_VAR?must be set}");
+    assert_eq!(result, Err(Error::RequiredVar("EMPTY_VAR".into(), "must be set".into())));
+}
+
+#[test]
+fn test_errors_disabled() {
+    use germi::{Config, Error};
+    let mut config = Config::default();
+    config.features.errors = false;
+    let germi = common::create_germi_with_config(config);
+
+    let result = germi.interpolate("${EMPTY_VAR:?must be set}").unwrap();
+    assert_eq!(result, "");
+
+    let result = germi.interpolate("${MISSING:?must be set}");
+    assert!(matches!(result, Err(Error::MissingVar(_))));
+}
+
+#[test]
+fn test_assign_default() {
+    let germi = create_germi();
+    // ${VAR:=default} - use and assign default if unset or empty
+    let result = germi.interpolate("${PORT:=8080} ${PORT}").unwrap();
+    assert_eq!(result, "8080 8080");
+
+    let result = germi.interpolate("${EMPTY_VAR:=filled}:$EMPTY_VAR").unwrap();
+    assert_eq!(result, "filled:filled");
+
+    let result = germi.interpolate("${TEST_VAR:=other}").unwrap();
+    assert_eq!(result, "test_value");
+}
+
+#[test]
+fn test_loose_assign_default() {
+    let germi = create_germi();
+    // ${VAR=default} - assign only if unset (empty is valid value)
+    let result = germi.interpolate("${EMPTY_VAR=filled}").unwrap();
+    assert_eq!(result, "");
+
+    let result = germi.interpolate("${MISSING=${TEST_VAR}} $MISSING").unwrap();
+    assert_eq!(result, "test_value test_value");
+}
+
+#[test]
+fn test_assign_scoped_to_call() {
+    let germi = create_germi();
+    germi.interpolate("${PORT:=8080}").unwrap();
+    assert!(germi.interpolate("${PORT}").is_err());
+}
+
+#[test]
+fn test_assign_persists_with_interpolate_mut() {
+    let mut germi = create_germi();
+    let result = germi.interpolate_mut("${PORT:=8080}").unwrap();
+    assert_eq!(result, "8080");
+
+    let result = germi.interpolate("port=$PORT").unwrap();
+    assert_eq!(result, "port=8080");
+}
+
+#[test]
+fn test_assignments_with_custom_provider() {
+    use std::collections::HashMap;
+
+    let mut germi = germi::Germi::with_provider(HashMap::from([("HOST".to_string(), "localhost".to_string())]));
+    let (result, assignments) = germi.interpolate_with_assignments("${HOST:=example.com}:${PORT:=8080}").unwrap();
+    assert_eq!(result, "localhost:8080");
+    assert_eq!(assignments, HashMap::from([("PORT".to_string(), "8080".to_string())]));
+
+    germi.provider_mut().extend(assignments);
+    assert_eq!(germi.interpolate("port=$PORT").unwrap(), "port=8080");
+}
+
+#[test]
+fn test_assignments_disabled() {
+    use germi::Config;
+    let mut config = Config::default();
+    config.features.assignments = false;
+    let germi = common::create_germi_with_config(config);
+
+    let result = germi.interpolate("${MISSING:=default}");
+    assert!(result.is_err());
+}
+
+#[test]
+fn test_string_length() {
+    let mut germi = create_germi();
+    germi.add_variable("UNICODE", "héllo");
+    // ${#VAR} - length of the resolved value in characters
+    assert_eq!(germi.interpolate("${#TEST_VAR}").unwrap(), "10");
+    assert_eq!(germi.interpolate("${#NESTED_VAR}").unwrap(), "10");
+    assert_eq!(germi.interpolate("${#EMPTY_VAR}").unwrap(), "0");
+    assert_eq!(germi.interpolate("${#UNICODE}").unwrap(), "5");
+    assert!(germi.interpolate("${#MISSING}").is_err());
+}
+
+#[test]
+fn test_lengths_disabled() {
+    use germi::Config;
+    let mut config = Config::default();
+    config.features.lengths = false;
+    let germi = common::create_germi_with_config(config);
+
+    let result = germi.interpolate("${#TEST_VAR}").unwrap();
+    assert_eq!(result, "test_value");
+}
@@ -104,10 +104,15 @@ fn test_complex_expression() {
 
 #[test]
 fn test_nested_variables_in_syntax() {
-    // ${A${B}} is treated as a single variable with name "A${B}"
-    // The scanner doesn't recursively parse nested variable names
+    // The name of ${A${B}} is only known after interpolation, so only B is reported
     let refs = find_variable_references("${A${B}}");
-    assert_eq!(refs, vec!["A${B}"]);
+    assert_eq!(refs, vec!["B"]);
+}
+
+#[test]
+fn test_dynamic_names() {
+    let refs = find_variable_references("${CONFIG_${ENV}} ${DB_${STAGE:-dev}_${REGION}:-x} ${!${PTR}}");
+    assert_eq!(refs, vec!["ENV", "PTR", "REGION", "STAGE"]);
 }
 
 #[test]
@@ -148,3 +153,59 @@ fn test_multiple_occurrences_same_variable() {
     );
     assert_eq!(refs, vec!["DB_HOST", "DB_USER"]);
 }
+
+#[test]
+fn test_with_error_modifier() {
+    let refs = find_variable_references("${VAR:?required} ${OTHER?required}");
+    assert_eq!(refs, vec!["OTHER", "VAR"]);
+}
+
+#[test]
+fn test_length_expansion() {
+    let refs = find_variable_references("${#SECRET}");
+    assert_eq!(refs, vec!["SECRET"]);
+}
+
+#[test]
+fn test_pattern_removal() {
+    let refs = find_variable_references("${FILE%.tar.gz} ${URL##*/}");
+    assert_eq!(refs, vec!["FILE", "URL"]);
+}
+
+#[test]
+fn test_pattern_replacement() {
+    let refs = find_variable_references("${HOST//./-} ${NAME/#app/svc}");
+    assert_eq!(refs, vec!["HOST", "NAME"]);
+}
+
+#[test]
+fn test_case_modification() {
+    let refs = find_variable_references("${NAME,,}-${ENV^^}");
+    assert_eq!(refs, vec!["ENV", "NAME"]);
+}
+
+#[test]
+fn test_positional_parameters_ignored() {
+    let refs = find_variable_references("$1 ${2:-dev} $@ $* ${#} $# ${10} $HOME");
+    assert_eq!(refs, vec!["HOME"]);
+}
+
+#[test]
+fn test_array_references() {
+    let refs = find_variable_references("${REGIONS[@]} ${#PORTS[@]} ${HOSTS[i-1]}");
+    assert_eq!(refs, vec!["HOSTS", "PORTS", "REGIONS"]);
+}
+
+#[test]
+fn test_array_subscript_references() {
+    let refs = find_variable_references("${ARR[$I]} ${ARR[${J}+1]:-none} ${${NAME}[0]}");
+    assert_eq!(refs, vec!["ARR", "I", "J", "NAME"]);
+}
+
+#[test]
+fn test_arithmetic_references() {
+    assert_eq!(find_variable_references("$(($BASE*2))"), vec!["BASE"]);
+    assert_eq!(find_variable_references("$((PORT+1))"), vec!["PORT"]);
+    let refs = find_variable_references("$((0x1F + 16#ff * ${COUNT:-1} - OFFSET_2 + $1))");
+    assert_eq!(refs, vec!["COUNT", "OFFSET_2"]);
+}
@@ -0,0 +1,81 @@
+mod common;
+use common::create_germi;
+use germi::{find_variable_references, Error};
+
+#[test]
+fn test_indirect_variable() {
+    let mut germi = create_germi();
+    germi.add_variable("DB_URL_PROD", "postgres://prod");
+    germi.add_variable("DB_URL_DEV", "postgres://dev");
+    germi.add_variable("TARGET", "DB_URL_PROD");
+    // ${!VAR} - expand the variable named by the value of VAR
+    assert_eq!(germi.interpolate("${!TARGET}").unwrap(), "postgres://prod");
+}
+
+#[test]
+fn test_indirect_with_modifiers() {
+    let mut germi = create_germi();
+    germi.add_variable("TARGET", "TEST_VAR");
+    germi.add_variable("DANGLING", "NOT_DEFINED");
+    assert_eq!(germi.interpolate("${!TARGET^^}").unwrap(), "TEST_VALUE");
+    assert_eq!(germi.interpolate("${!TARGET:0:4}").unwrap(), "test");
+    assert_eq!(germi.interpolate("${!DANGLING:-fallback}").unwrap(), "fallback");
+}
+
+#[test]
+fn test_indirect_missing() {
+    let mut germi = create_germi();
+    germi.add_variable("DANGLING", "NOT_DEFINED");
+    assert_eq!(germi.interpolate("${!MISSING}"), Err(Error::MissingVar("MISSING".into())));
+    assert_eq!(germi.interpolate("${!DANGLING}"), Err(Error::MissingVar("NOT_DEFINED".into())));
+}
+
+#[test]
+fn test_prefix_names() {
+    let mut germi = create_germi();
+    germi.add_variable("APP_PORT", "8080");
+    germi.add_variable("APP_HOST", "localhost");
+    germi.add_variable("OTHER", "x");
+    // ${!PREFIX*} and ${!PREFIX@} - sorted names of matching variables
+    assert_eq!(germi.interpolate("${!APP_*}").unwrap(), "APP_HOST APP_PORT");
+    assert_eq!(germi.interpolate("${!APP_@}").unwrap(), "APP_HOST APP_PORT");
+    assert_eq!(germi.interpolate("${!NONE_*}").unwrap(), "");
+}
+
+#[test]
+fn test_prefix_names_star_uses_ifs() {
+    let mut germi = create_germi();
+    germi.add_variable("APP_PORT", "8080");
+    germi.add_variable("APP_HOST", "localhost");
+    germi.add_variable("IFS", ",");
+    assert_eq!(germi.interpolate("${!APP_*}").unwrap(), "APP_HOST,APP_PORT");
+    assert_eq!(germi.interpolate("${!APP_@}").unwrap(), "APP_HOST APP_PORT");
+}
+
+#[test]
+fn test_prefix_names_include_temporary_vars() {
+    use std::collections::HashMap;
+    let mut germi = create_germi();
+    germi.add_variable("APP_PORT", "8080");
+    let mut extra = HashMap::new();
+    extra.insert("APP_DEBUG".to_string(), "1".to_string());
+    extra.insert("APP_PORT".to_string(), "9090".to_string());
+    assert_eq!(germi.interpolate_with("${!APP_*}", &extra).unwrap(), "APP_DEBUG APP_PORT");
+}
+
+#[test]
+fn test_indirection_references() {
+    let refs = find_variable_references("${!TARGET} ${!APP_*}");
+    assert_eq!(refs, vec!["TARGET"]);
+}
+
+#[test]
+fn test_indirection_disabled() {
+    use germi::Config;
+    let mut config = Config::default();
+    config.features.indirection = false;
+    let mut germi = common::create_germi_with_config(config);
+    germi.add_variable("TARGET", "TEST_VAR");
+
+    assert_eq!(germi.interpolate("${!TARGET}").unwrap(), "TEST_VAR");
+}
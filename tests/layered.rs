@@ -0,0 +1,52 @@
+use std::collections::HashMap;
+
+use germi::{Germi, LayeredProvider, SimpleContext, VariableProvider};
+
+fn map(vars: &[(&str, &str)]) -> HashMap<String, String> {
+    vars.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect()
+}
+
+fn create_layers() -> LayeredProvider {
+    LayeredProvider::new()
+        .with_layer("cli", map(&[("PORT", "9000")]))
+        .with_layer(".env.local", map(&[("HOST", "localhost"), ("PORT", "8081")]))
+        .with_layer(".env", map(&[("HOST", "example.com"), ("PORT", "8080"), ("NAME", "app")]))
+        .with_layer("defaults", map(&[("LOG", "info")]))
+}
+
+#[test]
+fn test_precedence() {
+    let germi = Germi::with_provider(create_layers());
+    assert_eq!(germi.interpolate("$NAME@$HOST:$PORT log=$LOG").unwrap(), "app@localhost:9000 log=info");
+}
+
+#[test]
+fn test_reports_source() {
+    let layers = create_layers();
+    assert_eq!(layers.source("PORT"), Some("cli"));
+    assert_eq!(layers.source("HOST"), Some(".env.local"));
+    assert_eq!(layers.source("LOG"), Some("defaults"));
+    assert_eq!(layers.source("MISSING"), None);
+    let (layer, value) = layers.get_with_source("NAME").unwrap();
+    assert_eq!((layer, value.as_ref()), (".env", "app"));
+    assert_eq!(layers.layer_names().collect::<Vec<_>>(), ["cli", ".env.local", ".env", "defaults"]);
+}
+
+#[test]
+fn test_keys_are_merged() {
+    let layers = create_layers();
+    let mut keys = layers.keys().unwrap();
+    keys.sort();
+    assert_eq!(keys, ["HOST", "LOG", "NAME", "PORT"]);
+}
+
+#[test]
+fn test_arrays_across_layers() {
+    let mut lists = SimpleContext::new();
+    lists.insert_array("REGIONS", ["us-east-1", "eu-west-1"]);
+    let mut layers = LayeredProvider::new().with_layer("lists", lists);
+    layers.push_layer("scalars", map(&[("REGIONS", "none"), ("ZONE", "a")]));
+    assert_eq!(layers.source("REGIONS"), Some("lists"));
+    let germi = Germi::with_provider(layers);
+    assert_eq!(germi.interpolate("$REGIONS ${#REGIONS[@]} $ZONE").unwrap(), "us-east-1 2 a");
+}
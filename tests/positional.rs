@@ -0,0 +1,66 @@
+mod common;
+use common::create_germi;
+use germi::Error;
+
+#[test]
+fn test_positional_parameters() {
+    let germi = create_germi();
+    let args = ["deploy.sh", "web", "prod"];
+    assert_eq!(germi.interpolate_with_args("run: deploy $1 --env ${2:-dev}", &args).unwrap(), "run: deploy web --env prod");
+    assert_eq!(germi.interpolate_with_args("$0 ${3:-dev} [${9-}]", &args).unwrap(), "deploy.sh dev []");
+    // Missing parameters are unset, like any other variable
+    assert!(matches!(germi.interpolate_with_args("$9", &args), Err(Error::MissingVar(_))));
+    assert_eq!(germi.interpolate_with_args("$#: $@", &args).unwrap(), "2: web prod");
+    // $10 is $1 followed by 0
+    assert_eq!(germi.interpolate_with_args("$10 ${10-}", &args).unwrap(), "web0 ");
+}
+
+#[test]
+fn test_braced_multi_digit() {
+    let germi = create_germi();
+    let args: Vec<String> = (0..=11).map(|i| format!("a{}", i)).collect();
+    assert_eq!(germi.interpolate_with_args("${10} ${11} ${#}", &args).unwrap(), "a10 a11 11");
+}
+
+#[test]
+fn test_at_and_star_joining() {
+    let mut germi = create_germi();
+    germi.add_variable("IFS", ",");
+    let args = ["prog", "a", "b c"];
+    assert_eq!(germi.interpolate_with_args("$@|$*", &args).unwrap(), "a b c|a,b c");
+    assert_eq!(germi.interpolate_with_args("${#@} ${@:2} ${*:0:2}", &args).unwrap(), "2 b c prog,a");
+    assert_eq!(germi.interpolate_with_args("${@: -1}", &args).unwrap(), "b c");
+    assert_eq!(germi.interpolate_with_args("${@:-none} ${#}", &["prog"]).unwrap(), "none 0");
+    assert_eq!(germi.interpolate_with_args("[$@] [$*]", &["prog"]).unwrap(), "[] []");
+}
+
+#[test]
+fn test_without_args_left_as_is() {
+    let germi = create_germi();
+    assert_eq!(germi.interpolate("costs $5, $@ and $#").unwrap(), "costs $5, $@ and $#");
+    // Braced forms behave like unset variables
+    assert_eq!(germi.interpolate("${1:-default}").unwrap(), "default");
+    assert!(matches!(germi.interpolate("${1}"), Err(Error::MissingVar(name)) if name == "1"));
+    assert!(matches!(germi.interpolate("${10}"), Err(Error::MissingVar(name)) if name == "10"));
+    // Lists of positional parameters are empty
+    assert_eq!(germi.interpolate("[${#@}] [${#*}] [${@^}] [${*,,}] [${@#x}] [${@/a/b}]").unwrap(), "[0] [0] [] [] [] []");
+}
+
+#[test]
+fn test_assign_to_positional_is_error() {
+    let germi = create_germi();
+    let result = germi.interpolate_with_args("${1:=x}", &["prog"]);
+    assert!(matches!(result, Err(Error::SyntaxError(_, 0))));
+}
+
+#[test]
+fn test_argv_quoted_at() {
+    let germi = create_germi();
+    let args = ["prog", "a b", "c"];
+    assert_eq!(germi.interpolate_argv_with_args(r#"cmd "$@" end"#, &args).unwrap(), ["cmd", "a b", "c", "end"]);
+    assert_eq!(germi.interpolate_argv_with_args(r#"cmd "x$@y""#, &args).unwrap(), ["cmd", "xa b", "cy"]);
+    assert_eq!(germi.interpolate_argv_with_args("cmd $@", &args).unwrap(), ["cmd", "a", "b", "c"]);
+    assert_eq!(germi.interpolate_argv_with_args(r#"cmd "$*""#, &args).unwrap(), ["cmd", "a b c"]);
+    // "$@" with no parameters gives no argument, unlike ""
+    assert_eq!(germi.interpolate_argv_with_args(r#"cmd "$@" """#, &["prog"]).unwrap(), ["cmd", ""]);
+}
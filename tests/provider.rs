@@ -0,0 +1,172 @@
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use germi::{Config, Error, Germi, LayeredProvider, VariableProvider};
+
+/// A provider backed by a fixed table, standing in for a database or secrets store.
+struct SecretsProvider {
+    secrets: HashMap<&'static str, &'static str>,
+    regions: Vec<&'static str>,
+}
+
+impl VariableProvider for SecretsProvider {
+    fn get_value(&self, key: &str) -> Option<Cow<'_, str>> {
+        self.secrets.get(key).map(|v| Cow::Borrowed(*v))
+    }
+
+    fn get_array(&self, key: &str) -> Option<Vec<Cow<'_, str>>> {
+        (key == "REGIONS").then(|| self.regions.iter().map(|r| Cow::Borrowed(*r)).collect())
+    }
+
+    fn special_value(&self, name: &str) -> Option<String> {
+        (name == "$").then(|| "4242".to_string())
+    }
+}
+
+fn secrets() -> SecretsProvider {
+    SecretsProvider {
+        secrets: HashMap::from([("DB_USER", "admin"), ("DB_PASS", "hunter2")]),
+        regions: vec!["us-east-1", "eu-west-1"],
+    }
+}
+
+#[test]
+fn test_custom_provider() {
+    let germi = Germi::with_provider(secrets());
+    assert_eq!(germi.interpolate("postgres://${DB_USER}:${DB_PASS}@db").unwrap(), "postgres://admin:hunter2@db");
+    assert_eq!(germi.interpolate("${REGIONS[@]} ${#REGIONS[@]}").unwrap(), "us-east-1 eu-west-1 2");
+    assert_eq!(germi.interpolate("pid $$").unwrap(), "pid 4242");
+    assert_eq!(germi.interpolate_argv("psql -U $DB_USER").unwrap(), ["psql", "-U", "admin"]);
+    assert_eq!(germi.provider().secrets.len(), 2);
+}
+
+#[test]
+fn test_custom_provider_with_config() {
+    let mut config = Config::default();
+    config.features.variables = false;
+    let germi = Germi::with_provider_and_config(secrets(), config);
+    assert_eq!(germi.interpolate("${DB_USER}").unwrap(), "${DB_USER}");
+}
+
+/// A provider that computes its values on every lookup.
+struct ComputedProvider;
+
+impl VariableProvider for ComputedProvider {
+    fn get_value(&self, key: &str) -> Option<Cow<'_, str>> {
+        match key {
+            "SECRET" => Some(Cow::Owned("s3cr3t".chars().rev().collect())),
+            "UPPER_USER" => Some(Cow::Owned("alice".to_uppercase())),
+            _ => None,
+        }
+    }
+
+    fn keys(&self) -> Option<Vec<Cow<'_, str>>> {
+        Some(vec![Cow::Owned("SECRET".to_string()), Cow::Borrowed("UPPER_USER")])
+    }
+
+    fn get_array(&self, key: &str) -> Option<Vec<Cow<'_, str>>> {
+        (key == "PORTS").then(|| (8080..8083).map(|port| Cow::Owned(port.to_string())).collect())
+    }
+}
+
+#[test]
+fn test_computed_values() {
+    let germi = Germi::with_provider(ComputedProvider);
+    assert_eq!(germi.interpolate("${UPPER_USER}:${SECRET}").unwrap(), "ALICE:t3rc3s");
+    assert_eq!(germi.interpolate("${PORTS[@]} ${PORTS[-1]} $PORTS").unwrap(), "8080 8081 8082 8082 8080");
+    assert_eq!(germi.interpolate("${!S*} ${!UP*}").unwrap(), "SECRET UPPER_USER");
+}
+
+#[test]
+fn test_simple_context_borrows() {
+    let mut context = germi::SimpleContext::new();
+    context.insert("KEY", "value");
+    context.insert_array("LIST", ["a"]);
+    assert!(matches!(context.get_value("KEY"), Some(Cow::Borrowed("value"))));
+    assert!(matches!(context.get_array("LIST").as_deref(), Some([Cow::Borrowed("a")])));
+}
+
+#[test]
+fn test_shared_and_boxed_providers() {
+    let shared = Arc::new(secrets());
+    let a = Germi::with_provider(Arc::clone(&shared));
+    let b = Germi::with_provider(&*shared);
+    assert_eq!(a.interpolate("$DB_USER").unwrap(), b.interpolate("$DB_USER").unwrap());
+
+    let boxed: Box<dyn VariableProvider> = Box::new(HashMap::from([("KEY".to_string(), "value".to_string())]));
+    let germi = Germi::with_provider(boxed);
+    assert_eq!(germi.interpolate("${KEY}").unwrap(), "value");
+}
+
+#[test]
+fn test_provider_mut() {
+    let mut germi = Germi::with_provider(HashMap::<String, String>::new());
+    germi.provider_mut().insert("NAME".to_string(), "world".to_string());
+    let extra = HashMap::from([("GREETING".to_string(), "hello".to_string())]);
+    assert_eq!(germi.interpolate_with("$GREETING $NAME", &extra).unwrap(), "hello world");
+}
+
+/// A provider that reads each variable from a file in a directory.
+struct FileProvider {
+    dir: std::path::PathBuf,
+}
+
+impl VariableProvider for FileProvider {
+    fn get_value(&self, key: &str) -> Option<Cow<'_, str>> {
+        self.try_get_value(key).ok().flatten()
+    }
+
+    fn try_get_value(&self, key: &str) -> Result<Option<Cow<'_, str>>, Box<dyn std::error::Error + Send + Sync>> {
+        match key {
+            "OPTIONAL" => Ok(None),
+            _ => Ok(Some(Cow::Owned(std::fs::read_to_string(self.dir.join(key))?))),
+        }
+    }
+}
+
+fn unreadable() -> FileProvider {
+    FileProvider { dir: "/nonexistent/germi".into() }
+}
+
+#[test]
+fn test_provider_error() {
+    let germi = Germi::with_provider(unreadable());
+    let err = germi.interpolate("token=${TOKEN:-none}").unwrap_err();
+    assert!(matches!(&err, Error::ProviderError { name, .. } if name == "TOKEN"));
+    assert!(err.to_string().starts_with("Failed to look up TOKEN: "));
+
+    // The underlying cause is preserved
+    let source = std::error::Error::source(&err).unwrap();
+    let io = source.downcast_ref::<std::io::Error>().unwrap();
+    assert_eq!(io.kind(), std::io::ErrorKind::NotFound);
+
+    assert!(germi.interpolate("$((TOKEN + 1))").is_err());
+    assert_eq!(germi.interpolate("${OPTIONAL:-none}").unwrap(), "none");
+    // Listing names does not look up the prefix
+    assert_eq!(germi.interpolate("[${!TOKEN@}]").unwrap(), "[]");
+}
+
+#[test]
+fn test_provider_error_in_layers() {
+    let overrides = HashMap::from([("TOKEN".to_string(), "abc".to_string())]);
+    let layers = LayeredProvider::new().with_layer("cli", overrides).with_layer("files", unreadable());
+    let germi = Germi::with_provider(layers);
+    assert_eq!(germi.interpolate("$TOKEN").unwrap(), "abc");
+    assert!(matches!(germi.interpolate("$OTHER"), Err(Error::ProviderError { .. })));
+}
+
+#[test]
+fn test_provider_error_in_layer_source() {
+    let defaults = HashMap::from([("TOKEN".to_string(), "default".to_string())]);
+    let layers = LayeredProvider::new().with_layer("files", unreadable()).with_layer("defaults", defaults);
+    // The failing layer is not skipped in favour of a lower one
+    assert!(matches!(layers.try_source("TOKEN"), Err(Error::ProviderError { name, .. }) if name == "TOKEN"));
+    assert!(matches!(layers.try_get_with_source("TOKEN"), Err(Error::ProviderError { .. })));
+    assert_eq!(layers.source("TOKEN"), None);
+    assert!(layers.get_with_source("TOKEN").is_none());
+    assert_eq!(layers.try_source("OPTIONAL").unwrap(), None);
+
+    let germi = Germi::with_provider(layers);
+    assert!(matches!(germi.interpolate("$TOKEN"), Err(Error::ProviderError { .. })));
+}
@@ -0,0 +1,109 @@
+mod common;
+use common::{create_germi, create_germi_with_config};
+use germi::{Config, Error};
+
+fn create_quoting_germi() -> germi::Germi {
+    let mut config = Config::default();
+    config.features.double_quotes = true;
+    create_germi_with_config(config)
+}
+
+#[test]
+fn test_quotes_kept_by_default() {
+    let germi = create_germi();
+    assert_eq!(germi.interpolate(r#""${TEST_VAR}""#).unwrap(), r#""test_value""#);
+    assert_eq!(germi.interpolate(r#"say "hi"#).unwrap(), r#"say "hi"#);
+    assert_eq!(germi.interpolate("it's").unwrap(), "it's");
+}
+
+#[test]
+fn test_double_quotes_removed() {
+    let germi = create_quoting_germi();
+    assert_eq!(germi.interpolate(r#""${TEST_VAR}""#).unwrap(), "test_value");
+    assert_eq!(germi.interpolate(r#"a"b c"d"#).unwrap(), "ab cd");
+    assert_eq!(germi.interpolate(r#""""#).unwrap(), "");
+    assert_eq!(germi.interpolate(r#""it's $TEST_VAR""#).unwrap(), "it's test_value");
+}
+
+#[test]
+fn test_double_quote_escapes() {
+    let germi = create_quoting_germi();
+    assert_eq!(germi.interpolate(r#""a \"b\" \\ \$TEST_VAR""#).unwrap(), r#"a "b" \ $TEST_VAR"#);
+    // Other backslashes are literal inside double quotes
+    assert_eq!(germi.interpolate(r#""a\nb\'""#).unwrap(), r"a\nb\'");
+    // ...but still escapes outside of them
+    assert_eq!(germi.interpolate(r#"a\n"b\n""#).unwrap(), "a\nb\\n");
+    assert_eq!(germi.interpolate(r#"\"x\""#).unwrap(), r#""x""#);
+}
+
+#[test]
+fn test_quotes_in_operator_words() {
+    let germi = create_quoting_germi();
+    assert_eq!(germi.interpolate(r#"${UNSET:-"a b"}"#).unwrap(), "a b");
+    assert_eq!(germi.interpolate(r#""${UNSET:-"$TEST_VAR"}""#).unwrap(), "test_value");
+}
+
+#[test]
+fn test_quotes_in_values_are_data() {
+    let mut germi = create_quoting_germi();
+    germi.add_variable("QUOTED", r#""it's""#);
+    assert_eq!(germi.interpolate("$QUOTED").unwrap(), r#""it's""#);
+    assert_eq!(germi.interpolate(r#""$QUOTED""#).unwrap(), r#""it's""#);
+}
+
+#[test]
+fn test_unclosed_quotes() {
+    let germi = create_quoting_germi();
+    assert_eq!(germi.interpolate(r#"ab "cd"#), Err(Error::UnclosedQuote(3)));
+    assert_eq!(germi.interpolate("it's"), Err(Error::UnclosedQuote(2)));
+    assert_eq!(germi.interpolate(r#""a\""#), Err(Error::UnclosedQuote(0)));
+}
+
+#[test]
+fn test_single_quotes_kept_by_default() {
+    let germi = create_germi();
+    assert_eq!(germi.interpolate("'$TEST_VAR' $TEST_VAR").unwrap(), "'$TEST_VAR' test_value");
+}
+
+#[test]
+fn test_strip_single_quotes() {
+    let germi = create_germi_with_config(Config {
+        strip_single_quotes: true,
+        ..Config::default()
+    });
+    assert_eq!(germi.interpolate("'$TEST_VAR' $TEST_VAR").unwrap(), "$TEST_VAR test_value");
+    assert_eq!(germi.interpolate("a'b'c''").unwrap(), "abc");
+    // The text inside is verbatim
+    assert_eq!(germi.interpolate(r"'a\nb' 'C:\users' a\nb").unwrap(), "a\\nb C:\\users a\nb");
+    assert_eq!(germi.interpolate(r"'it\'s'").unwrap(), r"it\'s");
+    // Unterminated quotes are kept as literal text
+    assert_eq!(germi.interpolate("it's").unwrap(), "it's");
+}
+
+#[test]
+fn test_strip_single_quotes_with_double_quotes() {
+    let mut config = Config {
+        strip_single_quotes: true,
+        ..Config::default()
+    };
+    config.features.double_quotes = true;
+    let germi = create_germi_with_config(config);
+    assert_eq!(germi.interpolate(r#"'"$TEST_VAR"' "'$TEST_VAR'""#).unwrap(), r#""$TEST_VAR" 'test_value'"#);
+}
+
+#[test]
+fn test_quoted_tokens() {
+    use germi::scanner::{Scanner, Token};
+
+    let mut scanner = Scanner::new("a'$B'c");
+    assert_eq!(scanner.scan_next().unwrap(), Some((Token::Literal("a"), 0..1)));
+    assert_eq!(scanner.scan_next().unwrap(), Some((Token::Quoted("$B"), 1..5)));
+    assert_eq!(scanner.scan_next().unwrap(), Some((Token::Literal("c"), 5..6)));
+    assert_eq!(scanner.scan_next().unwrap(), None);
+
+    let mut scanner = Scanner::with_quotes(r#""a'b""#);
+    assert_eq!(scanner.scan_next().unwrap(), Some((Token::DoubleQuote, 0..1)));
+    assert_eq!(scanner.scan_next().unwrap(), Some((Token::Literal("a'b"), 1..4)));
+    assert_eq!(scanner.scan_next().unwrap(), Some((Token::DoubleQuote, 4..5)));
+    assert_eq!(scanner.scan_next().unwrap(), None);
+}
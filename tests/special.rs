@@ -0,0 +1,46 @@
+mod common;
+use common::create_germi;
+use germi::Error;
+
+#[test]
+fn test_special_parameters_from_provider() {
+    let mut germi = create_germi();
+    germi.add_variable("$", "4242");
+    germi.add_variable("?", "1");
+    germi.add_variable("!", "4300");
+    germi.add_variable("-", "hB");
+    assert_eq!(germi.interpolate("/tmp/run.$$ status=$? bg=$! opts=$-").unwrap(), "/tmp/run.4242 status=1 bg=4300 opts=hB");
+    assert_eq!(germi.interpolate("${$}.log ${?}").unwrap(), "4242.log 1");
+}
+
+#[test]
+fn test_special_parameters_left_as_is() {
+    let germi = create_germi();
+    assert_eq!(germi.interpolate("Costs $$, really$? Yes$! a$-b").unwrap(), "Costs $$, really$? Yes$! a$-b");
+    assert!(matches!(germi.interpolate("${?}"), Err(Error::MissingVar(_))));
+}
+
+#[test]
+fn test_special_parameters_with_operators() {
+    let mut germi = create_germi();
+    germi.add_variable("?", "1");
+    germi.add_variable("-", "hB");
+    assert_eq!(germi.interpolate("${?:-x} ${-:-x} ${$:-x} ${!:-x}").unwrap(), "1 hB x x");
+    assert_eq!(germi.interpolate("${?:+failed} ${-#h} ${-/B/C}").unwrap(), "failed B hC");
+    assert!(matches!(germi.interpolate("${!?no background job}"), Err(Error::RequiredVar(name, _)) if name == "!"));
+    assert_eq!(germi.interpolate_with_args("${#:-x} ${#:+some}", &["cmd", "a", "b"]).unwrap(), "2 some");
+    // The length of a special parameter
+    assert_eq!(germi.interpolate("${#?} ${#-}").unwrap(), "1 2");
+}
+
+#[test]
+fn test_special_parameters_in_argv() {
+    let mut germi = create_germi();
+    germi.add_variable("$", "4242");
+    assert_eq!(germi.interpolate_argv("kill -0 $$ \"$$\"").unwrap(), ["kill", "-0", "4242", "4242"]);
+}
+
+#[test]
+fn test_special_not_a_reference() {
+    assert_eq!(germi::find_variable_references("$$ $? ${!} $- $USER"), vec!["USER"]);
+}
@@ -0,0 +1,251 @@
+mod common;
+use common::create_germi;
+use germi::Error;
+
+#[test]
+fn test_substring_offset() {
+    let mut germi = create_germi();
+    germi.add_variable("SHA", "3f2a9c1d8e7b");
+    // ${VAR:offset} - from offset to end
+    assert_eq!(germi.interpolate("${SHA:4}").unwrap(), "9c1d8e7b");
+    assert_eq!(germi.interpolate("${SHA:0}").unwrap(), "3f2a9c1d8e7b");
+    assert_eq!(germi.interpolate("${SHA:100}").unwrap(), "");
+}
+
+#[test]
+fn test_substring_offset_and_length() {
+    let mut germi = create_germi();
+    germi.add_variable("SHA", "3f2a9c1d8e7b");
+    // ${VAR:offset:length}
+    assert_eq!(germi.interpolate("${SHA:0:7}").unwrap(), "3f2a9c1");
+    assert_eq!(germi.interpolate("${SHA:2:3}").unwrap(), "2a9");
+    assert_eq!(germi.interpolate("${SHA:10:100}").unwrap(), "7b");
+}
+
+#[test]
+fn test_substring_negative() {
+    let germi = create_germi();
+    // A space is required so that ${VAR:-3} stays a default value
+    assert_eq!(germi.interpolate("${TEST_VAR: -5}").unwrap(), "value");
+    assert_eq!(germi.interpolate("${TEST_VAR: -5:-1}").unwrap(), "valu");
+    assert_eq!(germi.interpolate("${TEST_VAR:0:-6}").unwrap(), "test");
+    assert_eq!(germi.interpolate("${TEST_VAR: -20}").unwrap(), "");
+    assert_eq!(germi.interpolate("${TEST_VAR:-5}").unwrap(), "test_value");
+}
+
+#[test]
+fn test_substring_counts_chars() {
+    let mut germi = create_germi();
+    germi.add_variable("UNICODE", "héllo wörld");
+    assert_eq!(germi.interpolate("${UNICODE:1:4}").unwrap(), "éllo");
+    assert_eq!(germi.interpolate("${UNICODE: -5}").unwrap(), "wörld");
+}
+
+#[test]
+fn test_substring_interpolated_index() {
+    let mut germi = create_germi();
+    germi.add_variable("LEN", "4");
+    assert_eq!(germi.interpolate("${TEST_VAR:0:$LEN}").unwrap(), "test");
+}
+
+#[test]
+fn test_substring_arithmetic_index() {
+    let mut germi = create_germi();
+    germi.add_variable("N", "5");
+    assert_eq!(germi.interpolate("${TEST_VAR:1+1}").unwrap(), "st_value");
+    assert_eq!(germi.interpolate("${TEST_VAR:$N-1:N*2/5}").unwrap(), "_v");
+    assert_eq!(germi.interpolate("${TEST_VAR: -N}").unwrap(), "value");
+    // Unset names are 0, like in $((...))
+    assert_eq!(germi.interpolate("${TEST_VAR:abc}").unwrap(), "test_value");
+}
+
+#[test]
+fn test_substring_errors() {
+    let germi = create_germi();
+    assert!(matches!(germi.interpolate("${TEST_VAR:2:-20}"), Err(Error::SyntaxError(_, 0))));
+    assert!(matches!(germi.interpolate("x ${TEST_VAR:1+}"), Err(Error::ArithmeticError(_, 2))));
+    assert!(matches!(germi.interpolate("${MISSING:1}"), Err(Error::MissingVar(_))));
+}
+
+#[test]
+fn test_substrings_disabled() {
+    use germi::Config;
+    let mut config = Config::default();
+    config.features.substrings = false;
+    let germi = common::create_germi_with_config(config);
+
+    let result = germi.interpolate("${TEST_VAR:0:4}").unwrap();
+    assert_eq!(result, "test_value");
+}
+
+#[test]
+fn test_remove_prefix() {
+    let mut germi = create_germi();
+    germi.add_variable("URL", "https://example.com/a/b");
+    germi.add_variable("PATH_VAR", "/usr/local/bin");
+    // ${VAR#pattern} - shortest prefix, ${VAR##pattern} - longest prefix
+    assert_eq!(germi.interpolate("${URL#https://}").unwrap(), "example.com/a/b");
+    assert_eq!(germi.interpolate("${URL#*/}").unwrap(), "/example.com/a/b");
+    assert_eq!(germi.interpolate("${URL##*/}").unwrap(), "b");
+    assert_eq!(germi.interpolate("${PATH_VAR#/usr}").unwrap(), "/local/bin");
+    assert_eq!(germi.interpolate("${PATH_VAR#nomatch}").unwrap(), "/usr/local/bin");
+}
+
+#[test]
+fn test_remove_suffix() {
+    let mut germi = create_germi();
+    germi.add_variable("FILE", "backup.tar.gz");
+    // ${VAR%pattern} - shortest suffix, ${VAR%%pattern} - longest suffix
+    assert_eq!(germi.interpolate("${FILE%.tar.gz}").unwrap(), "backup");
+    assert_eq!(germi.interpolate("${FILE%.*}").unwrap(), "backup.tar");
+    assert_eq!(germi.interpolate("${FILE%%.*}").unwrap(), "backup");
+    assert_eq!(germi.interpolate("${FILE%.zip}").unwrap(), "backup.tar.gz");
+}
+
+#[test]
+fn test_remove_with_glob_classes() {
+    let mut germi = create_germi();
+    germi.add_variable("VERSION", "v1.2.3-rc1");
+    germi.add_variable("STARS", "**note**");
+    assert_eq!(germi.interpolate("${VERSION#[vV]}").unwrap(), "1.2.3-rc1");
+    assert_eq!(germi.interpolate("${VERSION%-rc[0-9]}").unwrap(), "v1.2.3");
+    assert_eq!(germi.interpolate("${VERSION%?}").unwrap(), "v1.2.3-rc");
+    assert_eq!(germi.interpolate("${VERSION#[!0-9]}").unwrap(), "1.2.3-rc1");
+    assert_eq!(germi.interpolate("${VERSION##*[[:alpha:]]}").unwrap(), "1");
+    assert_eq!(germi.interpolate("${STARS##[*]}").unwrap(), "*note**");
+}
+
+#[test]
+fn test_remove_pattern_is_interpolated() {
+    let mut germi = create_germi();
+    germi.add_variable("PREFIX", "test_");
+    germi.add_variable("EXT", "lue");
+    assert_eq!(germi.interpolate("${TEST_VAR#$PREFIX}").unwrap(), "value");
+    assert_eq!(germi.interpolate("${TEST_VAR%${EXT}}").unwrap(), "test_va");
+}
+
+#[test]
+fn test_remove_unicode() {
+    let mut germi = create_germi();
+    germi.add_variable("WORD", "héllo wörld");
+    assert_eq!(germi.interpolate("${WORD#h?}").unwrap(), "llo wörld");
+    assert_eq!(germi.interpolate("${WORD%w?rld}").unwrap(), "héllo ");
+}
+
+#[test]
+fn test_pattern_removal_disabled() {
+    use germi::Config;
+    let mut config = Config::default();
+    config.features.pattern_removal = false;
+    let germi = common::create_germi_with_config(config);
+
+    let result = germi.interpolate("${TEST_VAR#test_}").unwrap();
+    assert_eq!(result, "test_value");
+}
+
+#[test]
+fn test_replace_first_and_all() {
+    let mut germi = create_germi();
+    germi.add_variable("HOSTS", "a.example.com,b.example.com");
+    // ${VAR/pattern/rep} - first match, ${VAR//pattern/rep} - every match
+    assert_eq!(germi.interpolate("${HOSTS/example/test}").unwrap(), "a.test.com,b.example.com");
+    assert_eq!(germi.interpolate("${HOSTS//example/test}").unwrap(), "a.test.com,b.test.com");
+    assert_eq!(germi.interpolate("${HOSTS//,/ }").unwrap(), "a.example.com b.example.com");
+    assert_eq!(germi.interpolate("${HOSTS//.example.com}").unwrap(), "a,b");
+}
+
+#[test]
+fn test_replace_anchored() {
+    let mut germi = create_germi();
+    germi.add_variable("NAME", "app-app");
+    // ${VAR/#pattern/rep} - match at start, ${VAR/%pattern/rep} - match at end
+    assert_eq!(germi.interpolate("${NAME/#app/svc}").unwrap(), "svc-app");
+    assert_eq!(germi.interpolate("${NAME/%app/svc}").unwrap(), "app-svc");
+    assert_eq!(germi.interpolate("${NAME/#-/x}").unwrap(), "app-app");
+    assert_eq!(germi.interpolate("${NAME/#/prefix-}").unwrap(), "prefix-app-app");
+    assert_eq!(germi.interpolate("${NAME/%/-suffix}").unwrap(), "app-app-suffix");
+}
+
+#[test]
+fn test_replace_with_glob() {
+    let mut germi = create_germi();
+    germi.add_variable("PATH_VAR", "/usr/local/bin");
+    germi.add_variable("DIGITS", "a1b22c333");
+    // Matches are longest at the leftmost position
+    assert_eq!(germi.interpolate("${PATH_VAR/\\/*\\//X}").unwrap(), "Xbin");
+    assert_eq!(germi.interpolate("${PATH_VAR/l*l/L}").unwrap(), "/usr/L/bin");
+    assert_eq!(germi.interpolate("${DIGITS//[0-9]/#}").unwrap(), "a#b##c###");
+    assert_eq!(germi.interpolate("${DIGITS//[!0-9]}").unwrap(), "122333");
+    assert_eq!(germi.interpolate("${DIGITS/#a?/_}").unwrap(), "_b22c333");
+    assert_eq!(germi.interpolate("${DIGITS/%3*/_}").unwrap(), "a1b22c_");
+    assert_eq!(germi.interpolate("${DIGITS//*/all}").unwrap(), "all");
+}
+
+#[test]
+fn test_replace_is_interpolated() {
+    let mut germi = create_germi();
+    germi.add_variable("FROM", "value");
+    germi.add_variable("TO", "result");
+    assert_eq!(germi.interpolate("${TEST_VAR/$FROM/$TO}").unwrap(), "test_result");
+    assert_eq!(germi.interpolate("${TEST_VAR/${FROM}/${TO:-x/y}}").unwrap(), "test_result");
+    assert_eq!(germi.interpolate("${TEST_VAR/${MISSING:-_}/ }").unwrap(), "test value");
+}
+
+#[test]
+fn test_replace_unicode() {
+    let mut germi = create_germi();
+    germi.add_variable("WORD", "héllo wörld");
+    assert_eq!(germi.interpolate("${WORD//?ö/_}").unwrap(), "héllo _rld");
+    assert_eq!(germi.interpolate("${WORD//[éö]/e}").unwrap(), "hello werld");
+}
+
+#[test]
+fn test_pattern_replacement_disabled() {
+    use germi::Config;
+    let mut config = Config::default();
+    config.features.pattern_replacement = false;
+    let germi = common::create_germi_with_config(config);
+
+    let result = germi.interpolate("${TEST_VAR/test/prod}").unwrap();
+    assert_eq!(result, "test_value");
+}
+
+#[test]
+fn test_case_conversion() {
+    let mut germi = create_germi();
+    germi.add_variable("NAME", "my Service");
+    // ${VAR^} ${VAR^^} ${VAR,} ${VAR,,}
+    assert_eq!(germi.interpolate("${NAME^}").unwrap(), "My Service");
+    assert_eq!(germi.interpolate("${NAME^^}").unwrap(), "MY SERVICE");
+    assert_eq!(germi.interpolate("${NAME,}").unwrap(), "my Service");
+    assert_eq!(germi.interpolate("${NAME,,}").unwrap(), "my service");
+}
+
+#[test]
+fn test_case_conversion_unicode() {
+    let mut germi = create_germi();
+    germi.add_variable("WORD", "émile straße");
+    assert_eq!(germi.interpolate("${WORD^}").unwrap(), "Émile straße");
+    assert_eq!(germi.interpolate("${WORD^^}").unwrap(), "ÉMILE STRASSE");
+}
+
+#[test]
+fn test_case_conversion_with_pattern() {
+    let mut germi = create_germi();
+    germi.add_variable("NAME", "hello world");
+    // Only characters matching the pattern are converted
+    assert_eq!(germi.interpolate("${NAME^^[lo]}").unwrap(), "heLLO wOrLd");
+    assert_eq!(germi.interpolate("${NAME^h}").unwrap(), "Hello world");
+    assert_eq!(germi.interpolate("${NAME^w}").unwrap(), "hello world");
+}
+
+#[test]
+fn test_case_conversion_toggles() {
+    use germi::Config;
+    let mut config = Config::default();
+    config.features.upper_all = false;
+    let germi = common::create_germi_with_config(config);
+
+    assert_eq!(germi.interpolate("${TEST_VAR^^}").unwrap(), "test_value");
+    assert_eq!(germi.interpolate("${TEST_VAR^}").unwrap(), "Test_value");
+}
@@ -0,0 +1,79 @@
+mod common;
+use common::{create_germi, create_germi_with_config};
+use germi::Config;
+
+fn create_tilde_germi() -> germi::Germi {
+    let mut config = Config::default();
+    config.features.tilde = true;
+    let mut germi = create_germi_with_config(config);
+    germi.set_user_dirs(|user: &str| match user {
+        "" => Some("/home/fallback".to_string()),
+        "alice" => Some("/home/alice".to_string()),
+        _ => None,
+    });
+    germi
+}
+
+#[test]
+fn test_tilde_disabled_by_default() {
+    let mut germi = create_germi();
+    germi.add_variable("HOME", "/home/me");
+    assert_eq!(germi.interpolate("~/bin").unwrap(), "~/bin");
+}
+
+#[test]
+fn test_tilde_home() {
+    let mut germi = create_tilde_germi();
+    germi.add_variable("HOME", "/home/me");
+    assert_eq!(germi.interpolate("~").unwrap(), "/home/me");
+    assert_eq!(germi.interpolate("~/bin").unwrap(), "/home/me/bin");
+    assert_eq!(germi.interpolate("cd ~/src").unwrap(), "cd /home/me/src");
+    assert_eq!(germi.interpolate("~/${TEST_VAR}").unwrap(), "/home/me/test_value");
+}
+
+#[test]
+fn test_tilde_fallback_resolver() {
+    let germi = create_tilde_germi();
+    assert_eq!(germi.interpolate("~/bin").unwrap(), "/home/fallback/bin");
+    assert_eq!(germi.interpolate("~alice/x").unwrap(), "/home/alice/x");
+    // Unknown users are left as-is
+    assert_eq!(germi.interpolate("~bob/x").unwrap(), "~bob/x");
+}
+
+#[test]
+fn test_tilde_pwd() {
+    let mut germi = create_tilde_germi();
+    assert_eq!(germi.interpolate("~+/x ~-").unwrap(), "~+/x ~-");
+
+    germi.add_variable("PWD", "/work");
+    germi.add_variable("OLDPWD", "/prev");
+    assert_eq!(germi.interpolate("~+/x ~-").unwrap(), "/work/x /prev");
+}
+
+#[test]
+fn test_tilde_only_at_word_start_or_after_colon() {
+    let mut germi = create_tilde_germi();
+    germi.add_variable("HOME", "/h");
+    assert_eq!(germi.interpolate("~/bin:/usr/bin:~/.local/bin").unwrap(), "/h/bin:/usr/bin:/h/.local/bin");
+    assert_eq!(germi.interpolate("a~/b x/~ ~$TEST_VAR").unwrap(), "a~/b x/~ ~test_value");
+    assert_eq!(germi.interpolate("'~/quoted'").unwrap(), "'~/quoted'");
+}
+
+#[test]
+fn test_tilde_in_assignment() {
+    let mut germi = create_tilde_germi();
+    germi.add_variable("HOME", "/h");
+    assert_eq!(germi.interpolate("PATH=~/bin:~/x").unwrap(), "PATH=/h/bin:/h/x");
+    assert_eq!(germi.interpolate("env _DIR=~alice").unwrap(), "env _DIR=/home/alice");
+    // Only after a variable name
+    assert_eq!(germi.interpolate("a-b=~/x =~/y 1x=~/z").unwrap(), "a-b=~/x =~/y 1x=~/z");
+}
+
+#[test]
+fn test_tilde_in_default_word_but_not_in_values() {
+    let mut germi = create_tilde_germi();
+    germi.add_variable("HOME", "/h");
+    germi.add_variable("DIR", "~/data");
+    assert_eq!(germi.interpolate("${UNSET:-~/cache}").unwrap(), "/h/cache");
+    assert_eq!(germi.interpolate("$DIR").unwrap(), "~/data");
+}